@@ -0,0 +1,72 @@
+use clap::Parser;
+use std::time::Duration;
+use strecke::analysis::Analyzer;
+use strecke::game::GameHistory;
+
+#[derive(Parser)]
+struct Args {
+    /// JSON file holding a game history. Reads from --db instead if omitted.
+    history: Option<String>,
+    /// SQLite database written by the web server.
+    #[clap(long, default_value = "strecke.db")]
+    db: String,
+    /// ID of the finished game to load from --db.
+    #[clap(long)]
+    game: Option<i64>,
+    /// Print the report as JSON instead of text.
+    #[clap(long)]
+    json: bool,
+    /// Difficulty of the agents that play out each candidate move.
+    #[clap(long, default_value_t = 1)]
+    strength: usize,
+    /// Number of rollouts per candidate move.
+    #[clap(long, default_value_t = 8)]
+    rollouts: usize,
+    /// Milliseconds each rollout agent may spend choosing a move.
+    #[clap(long, default_value_t = 2)]
+    time_budget_ms: u64,
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+}
+
+fn load_history(
+    args: &Args,
+) -> Result<GameHistory, Box<dyn std::error::Error>> {
+    let json = match (&args.history, args.game) {
+        (Some(path), _) => std::fs::read_to_string(path)?,
+        (None, Some(game_id)) => {
+            let conn = rusqlite::Connection::open(&args.db)?;
+            conn.query_row(
+                "SELECT history FROM games WHERE id = ?1 LIMIT 1",
+                [game_id],
+                |row| row.get::<usize, Option<String>>(0),
+            )?
+            .ok_or("No history stored for this game")?
+        }
+        (None, None) => return Err("Pass a history file or --game".into()),
+    };
+    Ok(serde_json::from_str(&json)?)
+}
+
+fn main() {
+    let args = Args::parse();
+    let history = load_history(&args).unwrap_or_else(|e| {
+        eprintln!("Failed to load game: {}", e);
+        std::process::exit(1);
+    });
+    let analyzer = Analyzer {
+        strength: args.strength,
+        rollouts: args.rollouts,
+        time_budget: Duration::from_millis(args.time_budget_ms),
+        seed: args.seed,
+    };
+    let analysis = analyzer.analyze(&history).unwrap_or_else(|e| {
+        eprintln!("Failed to replay game: {}", e);
+        std::process::exit(1);
+    });
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&analysis).unwrap());
+    } else {
+        print!("{}", analysis);
+    }
+}
@@ -0,0 +1,92 @@
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+use strecke::agent;
+use strecke::board;
+use strecke::game::GameManager;
+use strecke::puzzle::{Objective, Puzzle};
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+enum Kind {
+    // Exactly one placement keeps the player alive, for --turns placements.
+    Survive,
+    // Exactly one placement knocks out two or more opponents.
+    Eliminate,
+    All,
+}
+
+#[derive(Parser)]
+struct Args {
+    /// Number of self-play games to search.
+    #[clap(short, long, default_value_t = 100)]
+    games: usize,
+    /// Number of players in each game.
+    #[clap(short, long, default_value_t = 4)]
+    players: usize,
+    /// Difficulty of the agents playing the games.
+    #[clap(long, default_value_t = 1)]
+    level: usize,
+    /// Milliseconds each agent may spend choosing a move.
+    #[clap(long, default_value_t = 20)]
+    time_budget_ms: u64,
+    #[clap(long, value_enum, default_value_t = Kind::All)]
+    kind: Kind,
+    /// Placements from their hand that survival puzzles must survive.
+    #[clap(long, default_value_t = 1)]
+    turns: usize,
+    /// Stop after finding this many puzzles.
+    #[clap(long)]
+    limit: Option<usize>,
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+fn main() {
+    let args = Args::parse();
+    let seed = args.seed.unwrap_or_else(|| rand::rng().random());
+    eprintln!("Using seed {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let agent = agent::create_agent(args.level);
+    let budget = Duration::from_millis(args.time_budget_ms);
+    let mut objectives = Vec::new();
+    if args.kind != Kind::Eliminate {
+        objectives.push(match args.turns {
+            0 | 1 => Objective::Survive,
+            turns => Objective::SurviveTurns { turns },
+        });
+    }
+    if args.kind != Kind::Survive {
+        objectives.push(Objective::Eliminate { opponents: 2 });
+    }
+    let limit = args.limit.unwrap_or(usize::MAX);
+    let mut found = 0;
+    for _ in 0..args.games {
+        let seats = board::random_seats(
+            args.players,
+            board::MIN_START_SPACING,
+            &mut rng,
+        );
+        let mut game = GameManager::new(&mut rng);
+        for (i, &pos) in seats.iter().enumerate() {
+            game.register_player(format!("p{}", i), board::edge_position(pos))
+                .unwrap();
+        }
+        while !game.is_over() {
+            for &objective in objectives.iter() {
+                if let Some(puzzle) = Puzzle::from_game(&game, objective) {
+                    println!("{}", serde_json::to_string(&puzzle).unwrap());
+                    found += 1;
+                    if found >= limit {
+                        return;
+                    }
+                }
+            }
+            let obs = game.current_observation();
+            let (idx, facing) =
+                agent.choose_action(&obs, Instant::now() + budget);
+            game.take_turn(idx, facing).unwrap();
+        }
+    }
+    eprintln!("Found {} puzzles", found);
+}
@@ -0,0 +1,23 @@
+use strecke::script::GameScript;
+
+// Plays out a scripted game file and prints the final state.
+fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("Usage: run_script <script.json>");
+        std::process::exit(2);
+    };
+    let script: GameScript = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to read {}: {}", path, e);
+            std::process::exit(1);
+        });
+    match script.run() {
+        Ok(outcome) => print!("{}", outcome.report()),
+        Err(e) => {
+            eprintln!("Script failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::parallel::GameJob;
+use std::collections::BTreeMap;
+use strecke::board::{EdgePos, NOT_READY};
+
+const NUM_POSITIONS: usize = NOT_READY as usize;
+
+#[derive(Default, Clone, Copy)]
+struct Tally {
+    players: usize,
+    survived: usize,
+}
+
+impl Tally {
+    fn add(&mut self, survived: bool) {
+        self.players += 1;
+        self.survived += survived as usize;
+    }
+    fn rate(&self) -> f64 {
+        self.survived as f64 / self.players.max(1) as f64
+    }
+}
+
+// Survival rates broken down by where players started, to see whether some
+// starting seats are stronger than others.
+pub struct FairnessReport {
+    by_position: [Tally; NUM_POSITIONS],
+    // Keyed by the distance to the nearest other player, in either direction.
+    by_nearest: BTreeMap<usize, Tally>,
+    // Keyed by the clockwise distance to the next player.
+    by_next: BTreeMap<usize, Tally>,
+}
+
+impl FairnessReport {
+    pub fn new() -> Self {
+        Self {
+            by_position: [Tally::default(); NUM_POSITIONS],
+            by_nearest: BTreeMap::new(),
+            by_next: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, job: &GameJob, elimination_turns: &[Option<usize>]) {
+        for (seat, &pos) in job.seats.iter().enumerate() {
+            let survived = elimination_turns[seat].is_none();
+            self.by_position[pos as usize].add(survived);
+            let gaps = job
+                .seats
+                .iter()
+                .enumerate()
+                .filter(|&(other, _)| other != seat)
+                .map(|(_, &other)| clockwise_distance(pos, other));
+            let Some(next) = gaps.clone().min() else {
+                continue;
+            };
+            let nearest = gaps.map(|d| d.min(NUM_POSITIONS - d)).min().unwrap();
+            self.by_next.entry(next).or_default().add(survived);
+            self.by_nearest.entry(nearest).or_default().add(survived);
+        }
+    }
+
+    pub fn print(&self) {
+        println!("Survival by starting position:");
+        println!(
+            "{:>4}  {:>6}  {:>6}  {:>9}",
+            "pos", "cell", "port", "survival"
+        );
+        for (pos, tally) in self.by_position.iter().enumerate() {
+            let (cell, port) = position_kind(pos as EdgePos);
+            println!(
+                "{:>4}  {:>6}  {:>6}  {}",
+                pos,
+                cell,
+                port,
+                format_tally(tally)
+            );
+        }
+
+        println!();
+        println!("Survival by type of starting position:");
+        let mut groups = BTreeMap::<(&str, &str), Tally>::new();
+        for (pos, tally) in self.by_position.iter().enumerate() {
+            let group =
+                groups.entry(position_kind(pos as EdgePos)).or_default();
+            group.players += tally.players;
+            group.survived += tally.survived;
+        }
+        for ((cell, port), tally) in groups.iter() {
+            println!("{:>6}  {:>6}  {}", cell, port, format_tally(tally));
+        }
+
+        println!();
+        println!("Survival by distance to the nearest opponent:");
+        for (dist, tally) in self.by_nearest.iter() {
+            println!("{:>4}  {}", dist, format_tally(tally));
+        }
+
+        println!();
+        println!("Survival by clockwise distance to the next opponent:");
+        for (dist, tally) in self.by_next.iter() {
+            println!("{:>4}  {}", dist, format_tally(tally));
+        }
+    }
+}
+
+fn format_tally(tally: &Tally) -> String {
+    format!(
+        "{:>8.1}%  ({} of {})",
+        100.0 * tally.rate(),
+        tally.survived,
+        tally.players
+    )
+}
+
+fn clockwise_distance(from: EdgePos, to: EdgePos) -> usize {
+    (to as usize + NUM_POSITIONS - from as usize) % NUM_POSITIONS
+}
+
+// Classifies a starting position by whether it's next to a corner cell of the
+// board, and whether it's the first or second port of its cell in clockwise
+// order.
+fn position_kind(pos: EdgePos) -> (&'static str, &'static str) {
+    let cell = (pos / 2) % 6;
+    let cell = if cell == 0 || cell == 5 {
+        "corner"
+    } else {
+        "middle"
+    };
+    let port = if pos % 2 == 0 { "first" } else { "second" };
+    (cell, port)
+}
@@ -0,0 +1,244 @@
+mod fairness;
+mod parallel;
+mod stats;
+mod tournament;
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+use strecke::agent::{self, Agent};
+use strecke::board;
+use strecke::external::ExternalAgent;
+use strecke::game::GameManager;
+
+#[derive(Parser)]
+struct Args {
+    #[clap(short, long, default_value_t = 1000)]
+    games: usize,
+    #[clap(short, long, value_delimiter = ',', default_value = "0,0,0,0")]
+    agents: Vec<usize>,
+    /// Command line for an external agent process, with shell-style quoting.
+    /// May be repeated; these players are seated after the built-in agents.
+    #[clap(long = "agent-cmd")]
+    agent_cmds: Vec<String>,
+    /// Milliseconds each agent may spend choosing a move.
+    #[clap(long, default_value_t = 100)]
+    time_budget_ms: u64,
+    /// Run a tournament between the agents, playing --games matches per
+    /// table, and report win rates with Elo and TrueSkill ratings instead of
+    /// per-game scores.
+    #[clap(long, value_enum)]
+    tournament: Option<tournament::Pairing>,
+    /// Number of players seated at each tournament table.
+    #[clap(long, default_value_t = 2)]
+    table_size: usize,
+    /// Number of rounds to play with Swiss pairings.
+    #[clap(long, default_value_t = 10)]
+    rounds: usize,
+    /// Number of worker threads. Defaults to the number of CPUs.
+    #[clap(long)]
+    threads: Option<usize>,
+    /// Master random seed. The same seed plays the same games regardless of
+    /// the number of threads, as long as agents don't run out of time.
+    #[clap(long)]
+    seed: Option<u64>,
+    /// Output format for per-game records.
+    #[clap(long, value_enum, default_value_t = stats::Format::Scores)]
+    format: stats::Format,
+    /// Print aggregate statistics as JSON instead of per-game records.
+    #[clap(long)]
+    summary: bool,
+    /// Report survival rates by starting position instead of per-game
+    /// records.
+    #[clap(long)]
+    fairness: bool,
+}
+
+impl Args {
+    fn agent_names(&self) -> Vec<String> {
+        let mut names = self
+            .agents
+            .iter()
+            .enumerate()
+            .map(|(i, level)| format!("#{} level {}", i, level))
+            .collect::<Vec<_>>();
+        for cmd in self.agent_cmds.iter() {
+            names.push(format!("#{} {}", names.len(), cmd));
+        }
+        names
+    }
+
+    fn make_agents(&self) -> Vec<Box<dyn Agent + Send>> {
+        let mut agents = self
+            .agents
+            .iter()
+            .map(|&level| agent::create_agent(level))
+            .collect::<Vec<_>>();
+        for cmd in self.agent_cmds.iter() {
+            let ext = ExternalAgent::spawn(cmd).expect("Failed to start agent");
+            agents.push(Box::new(ext));
+        }
+        agents
+    }
+}
+
+// The outcome of a finished self-play game.
+pub struct PlayedGame {
+    pub game: GameManager,
+    // Indices of the winning agents.
+    pub winners: Vec<usize>,
+    pub num_turns: usize,
+    // The turn on which each player was eliminated, if they were.
+    pub elimination_turns: Vec<Option<usize>>,
+    // Whether anyone had to wait for the dragon tile.
+    pub dragon_used: bool,
+}
+
+// Plays a full game with agents[i] starting from seats[i].
+pub fn play_game(
+    agents: &[&(dyn Agent + Send)],
+    seats: &[board::EdgePos],
+    min_spacing: board::EdgePos,
+    budget: Duration,
+    rng: &mut impl rand::Rng,
+) -> PlayedGame {
+    let mut game = GameManager::new(rng);
+    game.set_min_start_spacing(min_spacing).unwrap();
+    for (i, &pos) in seats.iter().enumerate() {
+        game.register_player(format!("p{}", i), board::edge_position(pos))
+            .unwrap();
+    }
+    let mut num_turns = 0;
+    let mut elimination_turns = vec![None; seats.len()];
+    let mut dragon_used = false;
+    loop {
+        let obs = game.current_observation();
+        dragon_used |= obs.dragon_holder.is_some();
+        let (tile_idx, facing) = agents[obs.board_index]
+            .choose_action(&obs, Instant::now() + budget);
+        let result = game.take_turn(tile_idx, facing).unwrap();
+        num_turns += 1;
+        for (i, trail) in game.board.players.iter().enumerate() {
+            if elimination_turns[i].is_none() && !trail.last().unwrap().alive {
+                elimination_turns[i] = Some(num_turns);
+            }
+        }
+        if let Some(result) = result {
+            let winners = result
+                .winners()
+                .iter()
+                .map(|name| name[1..].parse().unwrap())
+                .collect();
+            return PlayedGame {
+                game,
+                winners,
+                num_turns,
+                elimination_turns,
+                dragon_used,
+            };
+        }
+    }
+}
+
+// Picks random starting positions for the given number of players, as far
+// apart as the lobby requires by default.
+pub fn random_seats(
+    num_players: usize,
+    rng: &mut impl rand::Rng,
+) -> Vec<board::EdgePos> {
+    board::random_seats(num_players, board::MIN_START_SPACING, rng)
+}
+
+fn main() {
+    // Run with RUST_LOG=info to see log messages.
+    pretty_env_logger::init();
+
+    let args = Args::parse();
+    let seed = args.seed.unwrap_or_else(|| rand::rng().random());
+    eprintln!("Using seed {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let threads = args.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    });
+    let runner = parallel::Runner {
+        threads,
+        budget: Duration::from_millis(args.time_budget_ms),
+        make_agents: &|| args.make_agents(),
+    };
+    let names = args.agent_names();
+
+    if let Some(pairing) = args.tournament {
+        let mut t = tournament::Tournament::new(names, args.table_size);
+        match pairing {
+            tournament::Pairing::RoundRobin => {
+                t.run_round_robin(&runner, args.games, &mut rng)
+            }
+            tournament::Pairing::Swiss => {
+                t.run_swiss(&runner, args.rounds, args.games, &mut rng)
+            }
+        }
+        t.print_report();
+        return;
+    }
+
+    let num_players = names.len();
+    // The fairness report wants every gap between players, down to sharing a
+    // port, so it seats them with no spacing at all.
+    let min_spacing = if args.fairness {
+        0
+    } else {
+        board::MIN_START_SPACING
+    };
+    let jobs = (0..args.games)
+        .map(|_| parallel::GameJob {
+            entrants: (0..num_players).collect(),
+            seats: board::random_seats(num_players, min_spacing, &mut rng),
+            min_spacing,
+            seed: rng.random(),
+        })
+        .collect::<Vec<_>>();
+    if args.fairness {
+        let mut report = fairness::FairnessReport::new();
+        runner.run(
+            &jobs,
+            |_, _, played| played.elimination_turns.clone(),
+            |idx, elimination_turns| report.add(&jobs[idx], &elimination_turns),
+        );
+        report.print();
+        return;
+    }
+    if args.summary {
+        let mut summary = stats::SummaryBuilder::new(names.clone());
+        runner.run(
+            &jobs,
+            |idx, job, played| stats::GameRecord::new(idx, job, &names, played),
+            |idx, record| summary.add(&jobs[idx], &record),
+        );
+        let summary = serde_json::to_string_pretty(&summary.build()).unwrap();
+        println!("{}", summary);
+        return;
+    }
+    if args.format == stats::Format::Csv {
+        println!("{}", stats::GameRecord::csv_header());
+    }
+    runner.run(
+        &jobs,
+        |idx, job, played| match args.format {
+            stats::Format::Scores => played
+                .game
+                .player_scores()
+                .into_iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+            stats::Format::Csv => {
+                stats::GameRecord::new(idx, job, &names, played).to_csv()
+            }
+            stats::Format::Jsonl => serde_json::to_string(
+                &stats::GameRecord::new(idx, job, &names, played),
+            )
+            .unwrap(),
+        },
+        |_, line| println!("{}", line),
+    );
+}
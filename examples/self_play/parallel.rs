@@ -0,0 +1,80 @@
+use crate::{PlayedGame, play_game};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use strecke::agent::Agent;
+use strecke::board::EdgePos;
+
+// One game to be played, with agent entrants[i] starting from seats[i].
+pub struct GameJob {
+    pub entrants: Vec<usize>,
+    pub seats: Vec<EdgePos>,
+    // How far apart the seats were picked, which the game then enforces.
+    pub min_spacing: EdgePos,
+    // Seeds the tile shuffle, so a job plays out the same way on any thread.
+    pub seed: u64,
+}
+
+pub struct Runner<'a> {
+    pub threads: usize,
+    pub budget: Duration,
+    // Builds a fresh set of agents for each worker thread.
+    pub make_agents: &'a (dyn Fn() -> Vec<Box<dyn Agent + Send>> + Sync),
+}
+
+impl Runner<'_> {
+    // Plays all the jobs on a pool of worker threads. The `summarize` function
+    // runs on the workers, and `report` receives its results in job order.
+    pub fn run<T: Send>(
+        &self,
+        jobs: &[GameJob],
+        summarize: impl Fn(usize, &GameJob, &PlayedGame) -> T + Sync,
+        mut report: impl FnMut(usize, T),
+    ) {
+        let next_job = AtomicUsize::new(0);
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|s| {
+            for _ in 0..self.threads.max(1) {
+                let tx = tx.clone();
+                let next_job = &next_job;
+                let summarize = &summarize;
+                s.spawn(move || {
+                    let agents = (self.make_agents)();
+                    loop {
+                        let idx = next_job.fetch_add(1, Ordering::Relaxed);
+                        let Some(job) = jobs.get(idx) else { break };
+                        let table: Vec<&(dyn Agent + Send)> =
+                            job.entrants.iter().map(|&e| &*agents[e]).collect();
+                        let mut rng = StdRng::seed_from_u64(job.seed);
+                        let played = play_game(
+                            &table,
+                            &job.seats,
+                            job.min_spacing,
+                            self.budget,
+                            &mut rng,
+                        );
+                        let summary = summarize(idx, job, &played);
+                        if tx.send((idx, summary)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(tx);
+            // Hold on to results that finish early until it's their turn.
+            let mut pending = HashMap::new();
+            let mut next = 0;
+            for (idx, summary) in rx {
+                pending.insert(idx, summary);
+                while let Some(summary) = pending.remove(&next) {
+                    report(next, summary);
+                    next += 1;
+                }
+            }
+        });
+    }
+}
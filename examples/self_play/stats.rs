@@ -0,0 +1,195 @@
+use crate::PlayedGame;
+use crate::parallel::GameJob;
+use serde::Serialize;
+use strecke::board::EdgePos;
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    // Comma-separated player scores, one game per line.
+    Scores,
+    // One CSV row per game, with list fields separated by semicolons.
+    Csv,
+    // One JSON object per game.
+    Jsonl,
+}
+
+// Everything we record about a single self-play game. Per-seat fields are
+// indexed by seat, in the order players were registered.
+#[derive(Serialize)]
+pub struct GameRecord {
+    pub game: usize,
+    pub seed: u64,
+    pub agents: Vec<String>,
+    pub start_positions: Vec<EdgePos>,
+    // Seats in the order they were eliminated.
+    pub elimination_order: Vec<usize>,
+    // The turn on which each seat was eliminated, if it was.
+    pub elimination_turns: Vec<Option<usize>>,
+    // Number of tile paths each seat's token traveled along.
+    pub trail_lengths: Vec<usize>,
+    pub num_turns: usize,
+    pub dragon_used: bool,
+    pub winners: Vec<usize>,
+}
+
+impl GameRecord {
+    pub fn new(
+        game: usize,
+        job: &GameJob,
+        names: &[String],
+        played: &PlayedGame,
+    ) -> Self {
+        let mut elimination_order: Vec<usize> = (0..job.seats.len())
+            .filter(|&i| played.elimination_turns[i].is_some())
+            .collect();
+        elimination_order.sort_by_key(|&i| played.elimination_turns[i]);
+        Self {
+            game,
+            seed: job.seed,
+            agents: job.entrants.iter().map(|&e| names[e].clone()).collect(),
+            start_positions: job.seats.clone(),
+            elimination_order,
+            elimination_turns: played.elimination_turns.clone(),
+            trail_lengths: played
+                .game
+                .board
+                .players
+                .iter()
+                .map(|trail| trail.len() - 1)
+                .collect(),
+            num_turns: played.num_turns,
+            dragon_used: played.dragon_used,
+            winners: played.winners.clone(),
+        }
+    }
+
+    pub fn csv_header() -> &'static str {
+        "game,seed,agents,start_positions,elimination_order,\
+        elimination_turns,trail_lengths,num_turns,dragon_used,winners"
+    }
+
+    pub fn to_csv(&self) -> String {
+        let turns = self.elimination_turns.iter().map(|t| match t {
+            Some(t) => t.to_string(),
+            None => String::new(),
+        });
+        [
+            self.game.to_string(),
+            self.seed.to_string(),
+            // Agent names may contain commas, so quote them.
+            format!("\"{}\"", self.agents.join(";").replace('"', "\"\"")),
+            join(self.start_positions.iter()),
+            join(self.elimination_order.iter()),
+            join(turns),
+            join(self.trail_lengths.iter()),
+            self.num_turns.to_string(),
+            self.dragon_used.to_string(),
+            join(self.winners.iter()),
+        ]
+        .join(",")
+    }
+}
+
+fn join<T: ToString>(items: impl Iterator<Item = T>) -> String {
+    items.map(|x| x.to_string()).collect::<Vec<_>>().join(";")
+}
+
+// Aggregate statistics over many games with the same agents.
+#[derive(Serialize)]
+pub struct Summary {
+    games: usize,
+    mean_turns: f64,
+    dragon_rate: f64,
+    no_winner_rate: f64,
+    agents: Vec<AgentSummary>,
+}
+
+#[derive(Serialize)]
+struct AgentSummary {
+    name: String,
+    games: usize,
+    // Shared wins count fractionally.
+    wins: f64,
+    win_rate: f64,
+    mean_trail_length: f64,
+    // Mean turn of elimination, among games where this agent was eliminated.
+    mean_elimination_turn: Option<f64>,
+}
+
+pub struct SummaryBuilder {
+    games: usize,
+    turns: usize,
+    dragon_games: usize,
+    no_winner_games: usize,
+    names: Vec<String>,
+    agent_games: Vec<usize>,
+    wins: Vec<f64>,
+    trail_lengths: Vec<usize>,
+    eliminations: Vec<usize>,
+    elimination_turns: Vec<usize>,
+}
+
+impl SummaryBuilder {
+    pub fn new(names: Vec<String>) -> Self {
+        let n = names.len();
+        Self {
+            games: 0,
+            turns: 0,
+            dragon_games: 0,
+            no_winner_games: 0,
+            names,
+            agent_games: vec![0; n],
+            wins: vec![0.0; n],
+            trail_lengths: vec![0; n],
+            eliminations: vec![0; n],
+            elimination_turns: vec![0; n],
+        }
+    }
+
+    pub fn add(&mut self, job: &GameJob, record: &GameRecord) {
+        self.games += 1;
+        self.turns += record.num_turns;
+        self.dragon_games += record.dragon_used as usize;
+        self.no_winner_games += record.winners.is_empty() as usize;
+        for (seat, &e) in job.entrants.iter().enumerate() {
+            self.agent_games[e] += 1;
+            self.trail_lengths[e] += record.trail_lengths[seat];
+            if record.winners.contains(&seat) {
+                self.wins[e] += 1.0 / record.winners.len() as f64;
+            }
+            if let Some(turn) = record.elimination_turns[seat] {
+                self.eliminations[e] += 1;
+                self.elimination_turns[e] += turn;
+            }
+        }
+    }
+
+    pub fn build(self) -> Summary {
+        let games = self.games.max(1) as f64;
+        let agents = (0..self.names.len())
+            .map(|e| {
+                let n = self.agent_games[e].max(1) as f64;
+                AgentSummary {
+                    name: self.names[e].clone(),
+                    games: self.agent_games[e],
+                    wins: self.wins[e],
+                    win_rate: self.wins[e] / n,
+                    mean_trail_length: self.trail_lengths[e] as f64 / n,
+                    mean_elimination_turn: (self.eliminations[e] > 0).then(
+                        || {
+                            self.elimination_turns[e] as f64
+                                / self.eliminations[e] as f64
+                        },
+                    ),
+                }
+            })
+            .collect();
+        Summary {
+            games: self.games,
+            mean_turns: self.turns as f64 / games,
+            dragon_rate: self.dragon_games as f64 / games,
+            no_winner_rate: self.no_winner_games as f64 / games,
+            agents,
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use crate::parallel::{GameJob, Runner};
+use crate::random_seats;
+use rand::Rng;
+use strecke::board::MIN_START_SPACING;
+
+// How much a single game can move an Elo rating.
+const ELO_K: f64 = 16.0;
+const INITIAL_ELO: f64 = 1500.0;
+
+// TrueSkill's usual defaults: skill starts at 25 +/- 25/3, beta is the spread
+// of a single performance, and tau keeps ratings from freezing.
+const INITIAL_MU: f64 = 25.0;
+const INITIAL_SIGMA: f64 = INITIAL_MU / 3.0;
+const BETA: f64 = INITIAL_SIGMA / 2.0;
+const TAU: f64 = INITIAL_SIGMA / 100.0;
+// Gives two equal players about a 10% chance of drawing.
+const DRAW_MARGIN: f64 = 0.74;
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum Pairing {
+    // Every combination of entrants plays at a table together.
+    RoundRobin,
+    // Each round, entrants with similar ratings are seated together.
+    Swiss,
+}
+
+#[derive(Default)]
+struct Record {
+    games: usize,
+    // Shared wins count fractionally.
+    wins: f64,
+}
+
+#[derive(Clone, Copy)]
+struct Skill {
+    mu: f64,
+    sigma: f64,
+}
+
+pub struct Tournament {
+    names: Vec<String>,
+    table_size: usize,
+    records: Vec<Record>,
+    elo: Vec<f64>,
+    skill: Vec<Skill>,
+}
+
+impl Tournament {
+    pub fn new(names: Vec<String>, table_size: usize) -> Self {
+        assert!(table_size >= 2, "Tables need at least two players");
+        assert!(
+            names.len() >= table_size,
+            "Not enough agents to fill a table"
+        );
+        let n = names.len();
+        Self {
+            names,
+            table_size,
+            records: (0..n).map(|_| Record::default()).collect(),
+            elo: vec![INITIAL_ELO; n],
+            skill: vec![
+                Skill {
+                    mu: INITIAL_MU,
+                    sigma: INITIAL_SIGMA,
+                };
+                n
+            ],
+        }
+    }
+
+    pub fn run_round_robin(
+        &mut self,
+        runner: &Runner,
+        matches: usize,
+        rng: &mut impl Rng,
+    ) {
+        let mut jobs = Vec::new();
+        for table in combinations(self.names.len(), self.table_size) {
+            add_matches(&mut jobs, &table, matches, rng);
+        }
+        self.play(runner, &jobs);
+    }
+
+    pub fn run_swiss(
+        &mut self,
+        runner: &Runner,
+        rounds: usize,
+        matches: usize,
+        rng: &mut impl Rng,
+    ) {
+        for _ in 0..rounds {
+            let mut order: Vec<usize> = (0..self.names.len()).collect();
+            order.sort_by(|&a, &b| self.elo[b].total_cmp(&self.elo[a]));
+            // Anyone left over without a full table sits out this round.
+            let mut jobs = Vec::new();
+            for table in order.chunks_exact(self.table_size) {
+                add_matches(&mut jobs, table, matches, rng);
+            }
+            self.play(runner, &jobs);
+        }
+    }
+
+    fn play(&mut self, runner: &Runner, jobs: &[GameJob]) {
+        runner.run(
+            jobs,
+            |_, _, played| played.winners.clone(),
+            |idx, winners| {
+                let table = &jobs[idx].entrants;
+                let winners: Vec<usize> =
+                    winners.into_iter().map(|i| table[i]).collect();
+                self.record_game(table, &winners);
+            },
+        );
+    }
+
+    fn record_game(&mut self, table: &[usize], winners: &[usize]) {
+        for &e in table {
+            self.records[e].games += 1;
+            if winners.contains(&e) {
+                self.records[e].wins += 1.0 / winners.len() as f64;
+            }
+        }
+        // Treat the game as a set of pairwise results: winners beat losers,
+        // and everyone else draws.
+        let old_elo = self.elo.clone();
+        let k = ELO_K / (table.len() - 1) as f64;
+        for &a in table {
+            for &b in table {
+                if a == b {
+                    continue;
+                }
+                let actual = match (winners.contains(&a), winners.contains(&b))
+                {
+                    (true, false) => 1.0,
+                    (false, true) => 0.0,
+                    _ => 0.5,
+                };
+                let expected =
+                    1.0 / (1.0 + 10f64.powf((old_elo[b] - old_elo[a]) / 400.0));
+                self.elo[a] += k * (actual - expected);
+            }
+        }
+        self.update_skill(table, winners);
+    }
+
+    // TrueSkill, using the same pairwise results as Elo. Each pair gets the
+    // two-player update, and each player's share is averaged over their
+    // opponents so a bigger table doesn't count for more.
+    fn update_skill(&mut self, table: &[usize], winners: &[usize]) {
+        let old_skill = self.skill.clone();
+        let pairs = (table.len() - 1) as f64;
+        for &a in table {
+            let sa = old_skill[a];
+            let var_a = sa.sigma * sa.sigma + TAU * TAU;
+            let mut mean_shift = 0.0;
+            let mut var_shrink = 0.0;
+            for &b in table {
+                if a == b {
+                    continue;
+                }
+                let sb = old_skill[b];
+                let var_b = sb.sigma * sb.sigma + TAU * TAU;
+                let c = (2.0 * BETA * BETA + var_a + var_b).sqrt();
+                let t = (sa.mu - sb.mu) / c;
+                let e = DRAW_MARGIN / c;
+                let (v, w) = match (winners.contains(&a), winners.contains(&b))
+                {
+                    (true, false) => win_factors(t, e),
+                    (false, true) => {
+                        let (v, w) = win_factors(-t, e);
+                        (-v, w)
+                    }
+                    _ => draw_factors(t, e),
+                };
+                mean_shift += var_a / c * v;
+                var_shrink += var_a / (c * c) * w;
+            }
+            self.skill[a] = Skill {
+                mu: sa.mu + mean_shift / pairs,
+                sigma: (var_a * (1.0 - var_shrink / pairs)).sqrt(),
+            };
+        }
+    }
+
+    pub fn print_report(&self) {
+        let mut order: Vec<usize> = (0..self.names.len()).collect();
+        order.sort_by(|&a, &b| self.elo[b].total_cmp(&self.elo[a]));
+        let width = self
+            .names
+            .iter()
+            .map(|name| name.len())
+            .max()
+            .unwrap_or(0)
+            .max(5);
+        println!(
+            "{:width$}  {:>6}  {:>8}  {:>6}  {:>15}  {:>6}  {:>12}",
+            "agent", "games", "wins", "win%", "95% CI", "elo", "trueskill"
+        );
+        for e in order {
+            let rec = &self.records[e];
+            let (lo, hi) = wilson_interval(rec.wins, rec.games);
+            let rate = if rec.games == 0 {
+                0.0
+            } else {
+                rec.wins / rec.games as f64
+            };
+            let ci = format!("{:.1}% - {:.1}%", 100.0 * lo, 100.0 * hi);
+            let skill = self.skill[e];
+            let ts = format!("{:.1} +/- {:.1}", skill.mu, skill.sigma);
+            println!(
+                "{:width$}  {:>6}  {:>8.1}  {:>5.1}%  {:>15}  {:>6.0}  {:>12}",
+                self.names[e],
+                rec.games,
+                rec.wins,
+                100.0 * rate,
+                ci,
+                self.elo[e],
+                ts,
+            );
+        }
+    }
+}
+
+// Queues up each match once per rotation of the seats, so every entrant gets a
+// turn at each of the starting positions with the same tiles.
+fn add_matches(
+    jobs: &mut Vec<GameJob>,
+    table: &[usize],
+    matches: usize,
+    rng: &mut impl Rng,
+) {
+    let n = table.len();
+    for _ in 0..matches {
+        let seats = random_seats(n, rng);
+        let seed = rng.random();
+        for rotation in 0..n {
+            jobs.push(GameJob {
+                entrants: (0..n).map(|i| table[(i + rotation) % n]).collect(),
+                seats: seats.clone(),
+                min_spacing: MIN_START_SPACING,
+                seed,
+            });
+        }
+    }
+}
+
+// All k-element subsets of 0..n, in lexicographic order.
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut result = Vec::new();
+    let mut combo: Vec<usize> = (0..k).collect();
+    loop {
+        result.push(combo.clone());
+        // Find the rightmost index that can still be advanced.
+        let Some(i) = (0..k).rev().find(|&i| combo[i] < n - k + i) else {
+            return result;
+        };
+        combo[i] += 1;
+        for j in (i + 1)..k {
+            combo[j] = combo[j - 1] + 1;
+        }
+    }
+}
+
+// 95% Wilson score interval for a win rate.
+fn wilson_interval(wins: f64, games: usize) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 1.0);
+    }
+    let z = 1.96;
+    let n = games as f64;
+    let p = wins / n;
+    let denom = 1.0 + z * z / n;
+    let center = (p + z * z / (2.0 * n)) / denom;
+    let margin = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / denom;
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+// Mean and variance factors for the player who won, where t is how far ahead
+// they were rated and e is the draw margin, both in units of c.
+fn win_factors(t: f64, e: f64) -> (f64, f64) {
+    let x = t - e;
+    let cdf = normal_cdf(x);
+    // Far into the tail the ratio tends to -x.
+    let v = if cdf > 1e-12 { normal_pdf(x) / cdf } else { -x };
+    (v, v * (v + x))
+}
+
+// Mean and variance factors for a drawn pair, from the first player's side.
+fn draw_factors(t: f64, e: f64) -> (f64, f64) {
+    let (lo, hi) = (-e - t, e - t);
+    let mass = normal_cdf(hi) - normal_cdf(lo);
+    if mass < 1e-12 {
+        // A draw this unlikely just pulls the two ratings together.
+        return (-t.signum() * (t.abs() - e), 1.0);
+    }
+    let v = (normal_pdf(lo) - normal_pdf(hi)) / mass;
+    let w = v * v + (hi * normal_pdf(hi) - lo * normal_pdf(lo)) / mass;
+    (v, w)
+}
+
+fn normal_pdf(x: f64) -> f64 {
+    (-x * x / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / std::f64::consts::SQRT_2)
+}
+
+// Complementary error function, good to about 1e-7 relative error even far
+// into the tails (Numerical Recipes' Chebyshev fit).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = [
+        -1.26551223,
+        1.00002368,
+        0.37409196,
+        0.09678418,
+        -0.18628806,
+        0.27886807,
+        -1.13520398,
+        1.48851587,
+        -0.82215223,
+        0.17087277,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |acc, &coef| acc * t + coef);
+    let ans = t * (-z * z + poly).exp();
+    if x >= 0.0 { ans } else { 2.0 - ans }
+}
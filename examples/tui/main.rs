@@ -0,0 +1,295 @@
+mod render;
+use clap::Parser;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::Stylize;
+use crossterm::{cursor, execute, queue, terminal};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use strecke::agent::{self, Agent};
+use strecke::board;
+use strecke::game::{GameManager, GameResult};
+use strecke::tiles::Direction;
+
+#[derive(Parser)]
+struct Args {
+    /// Difficulty levels of the computer opponents.
+    #[clap(short, long, value_delimiter = ',', default_value = "1,1")]
+    opponents: Vec<usize>,
+    /// Your starting position, from 0 to 47. Random if omitted.
+    #[clap(long)]
+    seat: Option<board::EdgePos>,
+    /// Milliseconds each opponent may spend choosing a move.
+    #[clap(long, default_value_t = 500)]
+    time_budget_ms: u64,
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+const HUMAN: &str = "you";
+
+// Puts the terminal into raw mode for as long as it's alive.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn new() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct App {
+    game: GameManager,
+    agents: Vec<Box<dyn Agent + Send>>,
+    budget: Duration,
+    // Facing of each tile in the human player's hand.
+    facings: Vec<Direction>,
+    selected: usize,
+    // What happened most recently, shown under the board.
+    log: Vec<String>,
+}
+
+impl App {
+    fn human_hand_size(&self) -> usize {
+        self.game
+            .get_player(HUMAN)
+            .map_or(0, |p| p.tiles_in_hand.len())
+    }
+
+    fn is_human_turn(&self) -> bool {
+        !self.game.is_over() && self.game.current_player().username == HUMAN
+    }
+
+    fn reset_hand(&mut self) {
+        self.facings = vec![Direction::North; self.human_hand_size()];
+        self.selected = self.selected.min(self.facings.len().saturating_sub(1));
+    }
+
+    fn play(&mut self, tile_idx: usize, facing: Direction) {
+        let name = self.game.current_player().username.clone();
+        let alive_before = self.game.alive_players.len();
+        let result = match self.game.take_turn(tile_idx, facing) {
+            Ok(result) => result,
+            Err(e) => {
+                self.log.push(e);
+                return;
+            }
+        };
+        self.log
+            .push(format!("{} played a tile facing {:?}.", name, facing));
+        let lost = alive_before - self.game.alive_players.len();
+        if lost > 0 {
+            self.log.push(format!("{} player(s) eliminated.", lost));
+        }
+        if let Some(result) = result {
+            self.log.push(match result {
+                GameResult::Winner { player } => {
+                    format!("Game over: {} won!", player)
+                }
+                GameResult::SharedWin { players } => {
+                    format!(
+                        "Game over: {} share the win!",
+                        players.join(" and ")
+                    )
+                }
+                GameResult::NoWinner => "Game over: everyone lost!".to_owned(),
+            });
+        }
+        self.reset_hand();
+    }
+
+    fn play_opponent(&mut self) {
+        let obs = self.game.current_observation();
+        // Opponents are registered after the human player.
+        let agent = &self.agents[obs.board_index - 1];
+        let (tile_idx, facing) =
+            agent.choose_action(&obs, Instant::now() + self.budget);
+        self.play(tile_idx, facing);
+    }
+
+    // Handles a key press. Returns false if the player wants to quit.
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        let hand_size = self.human_hand_size();
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Left | KeyCode::Char('h') if hand_size > 0 => {
+                self.selected = (self.selected + hand_size - 1) % hand_size;
+            }
+            KeyCode::Right | KeyCode::Char('l') if hand_size > 0 => {
+                self.selected = (self.selected + 1) % hand_size;
+            }
+            KeyCode::Char(c @ '1'..='3') => {
+                let idx = c as usize - '1' as usize;
+                if idx < hand_size {
+                    self.selected = idx;
+                }
+            }
+            KeyCode::Up | KeyCode::Char('r') if hand_size > 0 => {
+                let f = &mut self.facings[self.selected];
+                *f = rotate(*f, 1);
+            }
+            KeyCode::Down if hand_size > 0 => {
+                let f = &mut self.facings[self.selected];
+                *f = rotate(*f, 3);
+            }
+            KeyCode::Enter | KeyCode::Char(' ')
+                if self.is_human_turn() && hand_size > 0 =>
+            {
+                self.play(self.selected, self.facings[self.selected]);
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&self) -> io::Result<()> {
+        let mut lines = Vec::new();
+        let me = self.game.get_player(HUMAN);
+        let preview = me.filter(|_| self.is_human_turn()).map(|p| {
+            let pos = self.game.current_player_pos().next_tile_position();
+            render::Preview {
+                row: pos.row,
+                col: pos.col,
+                tile: &p.tiles_in_hand[self.selected],
+                facing: self.facings[self.selected],
+            }
+        });
+        lines.extend(render::draw_board(&self.game.board, preview).lines());
+        lines.push(String::new());
+        for (bidx, _) in self.game.board.players.iter().enumerate() {
+            let color =
+                render::PLAYER_COLORS[bidx % render::PLAYER_COLORS.len()];
+            let name = if bidx == 0 {
+                HUMAN.to_owned()
+            } else {
+                format!("bot {}", bidx)
+            };
+            let alive =
+                self.game.alive_players.iter().any(|p| p.username == name);
+            let status = if alive { "" } else { " (out)" };
+            lines.push(format!("{} {}{}", "@".with(color), name, status));
+        }
+        lines.push(String::new());
+        match me {
+            Some(p) if !p.tiles_in_hand.is_empty() => {
+                lines.push("Your tiles:".to_owned());
+                lines.extend(
+                    render::draw_hand(
+                        &p.tiles_in_hand,
+                        &self.facings,
+                        self.selected,
+                    )
+                    .lines(),
+                );
+            }
+            Some(_) => lines.push("Waiting for the dragon tile.".to_owned()),
+            None => lines.push("You're out.".to_owned()),
+        }
+        lines.push(String::new());
+        lines.extend(self.log.iter().rev().take(3).rev().cloned());
+        lines.push(String::new());
+        lines.push(
+            "<-/-> select  r/up rotate  enter play  q quit  \
+             (matching numbers are connected)"
+                .to_owned(),
+        );
+
+        let mut out = io::stdout();
+        queue!(
+            out,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(terminal::ClearType::All)
+        )?;
+        for line in lines {
+            write!(out, "{}\r\n", line)?;
+        }
+        out.flush()
+    }
+}
+
+// Turns a tile a quarter turn the given number of times, in the same order
+// as clicking a tile in the web client.
+fn rotate(facing: Direction, times: usize) -> Direction {
+    (0..times).fold(facing, |f, _| match f {
+        Direction::North => Direction::West,
+        Direction::West => Direction::South,
+        Direction::South => Direction::East,
+        Direction::East => Direction::North,
+    })
+}
+
+fn main() -> io::Result<()> {
+    let args = Args::parse();
+    let seed = args.seed.unwrap_or_else(|| rand::rng().random());
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    if let Some(seat) = args.seat
+        && (!board::is_valid_edge_position(seat) || seat == board::NOT_READY)
+    {
+        eprintln!("Seats go from 0 to 47");
+        std::process::exit(1);
+    }
+    let human_seat = args
+        .seat
+        .unwrap_or_else(|| rng.random_range(0..board::NOT_READY));
+    let Some(bot_seats) = board::spread_seats(
+        &[human_seat],
+        args.opponents.len(),
+        board::MIN_START_SPACING,
+        &mut rng,
+    ) else {
+        eprintln!("Too many opponents to seat");
+        std::process::exit(1);
+    };
+    let mut game = GameManager::new(&mut rng);
+    game.register_player(HUMAN.to_owned(), board::edge_position(human_seat))
+        .unwrap();
+    for (i, &seat) in bot_seats.iter().enumerate() {
+        let pos = board::edge_position(seat);
+        game.register_player(format!("bot {}", i + 1), pos).unwrap();
+    }
+    let mut app = App {
+        game,
+        agents: args
+            .opponents
+            .iter()
+            .map(|&level| agent::create_agent(level))
+            .collect(),
+        budget: Duration::from_millis(args.time_budget_ms),
+        facings: Vec::new(),
+        selected: 0,
+        log: vec![format!("Game {} started.", seed)],
+    };
+    app.reset_hand();
+
+    let _term = RawTerminal::new()?;
+    loop {
+        app.draw()?;
+        if !app.game.is_over() && !app.is_human_turn() {
+            // Give the human a moment to see each opponent's move.
+            std::thread::sleep(Duration::from_millis(300));
+            app.play_opponent();
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && (app.game.is_over() || !app.handle_key(key.code))
+        {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use crossterm::style::{Color, Stylize};
+use strecke::board::{Board, Position};
+use strecke::tiles::{Direction, Effect, Port, Tile};
+
+// Each cell is drawn as a box this many characters wide and tall, sharing its
+// border with its neighbors.
+const CELL_WIDTH: usize = 6;
+const CELL_HEIGHT: usize = 4;
+const PORTS: [Port; 8] = [
+    Port::A,
+    Port::B,
+    Port::C,
+    Port::D,
+    Port::E,
+    Port::F,
+    Port::G,
+    Port::H,
+];
+pub const PLAYER_COLORS: [Color; 8] = [
+    Color::Red,
+    Color::Blue,
+    Color::Green,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Yellow,
+    Color::White,
+    Color::DarkYellow,
+];
+
+// A grid of characters that can be drawn over and then printed.
+pub struct Canvas {
+    cells: Vec<Vec<(char, Option<Color>)>>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            cells: vec![vec![(' ', None); width]; height],
+        }
+    }
+
+    fn put(&mut self, x: usize, y: usize, ch: char, color: Option<Color>) {
+        if let Some(cell) = self.cells.get_mut(y).and_then(|r| r.get_mut(x)) {
+            *cell = (ch, color);
+        }
+    }
+
+    // Draws an empty cell with its top-left corner at (x, y).
+    fn draw_box(&mut self, x: usize, y: usize, color: Option<Color>) {
+        for dx in 0..=CELL_WIDTH {
+            let ch = if dx % CELL_WIDTH == 0 { '+' } else { '-' };
+            self.put(x + dx, y, ch, color);
+            self.put(x + dx, y + CELL_HEIGHT, ch, color);
+        }
+        for dy in 1..CELL_HEIGHT {
+            self.put(x, y + dy, '|', color);
+            self.put(x + CELL_WIDTH, y + dy, '|', color);
+        }
+    }
+
+    // Draws a tile inside the box at (x, y). Path ends that share a number
+    // are connected.
+    fn draw_tile(
+        &mut self,
+        x: usize,
+        y: usize,
+        tile: &Tile,
+        facing: Direction,
+        color: Option<Color>,
+    ) {
+        let mut labels: Vec<(Port, char)> = Vec::new();
+        for port in PORTS {
+            if labels.iter().any(|&(p, _)| p == port) {
+                continue;
+            }
+            let label = char::from(b'1' + (labels.len() / 2) as u8);
+            labels.push((port, label));
+            labels.push((tile.traverse(port, facing), label));
+        }
+        for (port, label) in labels {
+            let (dx, dy) = match port {
+                Port::A => (2, 1),
+                Port::B => (4, 1),
+                Port::C => (5, 1),
+                Port::D => (5, 3),
+                Port::E => (4, 3),
+                Port::F => (2, 3),
+                Port::G => (1, 3),
+                Port::H => (1, 1),
+            };
+            self.put(x + dx, y + dy, label, color);
+        }
+        // Power tiles get a mark in the middle.
+        match tile.effect {
+            Some(Effect::Spinner) => self.put(x + 3, y + 2, '*', color),
+            Some(Effect::Bridge) => self.put(x + 3, y + 2, '=', color),
+            None => {}
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&(ch, color)| match color {
+                        Some(c) => ch.with(c).to_string(),
+                        None => ch.to_string(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// Where a port sits on the border of cell (row, col), relative to the top
+// left corner of cell (0, 0). Adjacent cells share these points.
+fn port_point(row: i8, col: i8, port: Port) -> (i32, i32) {
+    let left = col as i32 * CELL_WIDTH as i32;
+    let top = row as i32 * CELL_HEIGHT as i32;
+    let (dx, dy) = match port {
+        Port::A => (2, 0),
+        Port::B => (4, 0),
+        Port::C => (6, 1),
+        Port::D => (6, 3),
+        Port::E => (4, 4),
+        Port::F => (2, 4),
+        Port::G => (0, 3),
+        Port::H => (0, 1),
+    };
+    (left + dx, top + dy)
+}
+
+// A tile to draw on the board that hasn't been played yet.
+pub struct Preview<'a> {
+    pub row: i8,
+    pub col: i8,
+    pub tile: &'a Tile,
+    pub facing: Direction,
+}
+
+pub fn draw_board(board: &Board, preview: Option<Preview>) -> Canvas {
+    let mut canvas = Canvas::new(6 * CELL_WIDTH + 1, 6 * CELL_HEIGHT + 1);
+    for row in 0..6 {
+        for col in 0..6 {
+            let (x, y) =
+                (col as usize * CELL_WIDTH, row as usize * CELL_HEIGHT);
+            canvas.draw_box(x, y, Some(Color::DarkGrey));
+            let pos = Position {
+                row,
+                col,
+                port: Port::A,
+                alive: true,
+            };
+            if let Some(Some((tile, facing))) = board.get_tile(&pos) {
+                canvas.draw_tile(x, y, tile, *facing, None);
+            }
+        }
+    }
+    if let Some(p) = preview {
+        let (x, y) =
+            (p.col as usize * CELL_WIDTH, p.row as usize * CELL_HEIGHT);
+        canvas.draw_box(x, y, Some(Color::Yellow));
+        canvas.draw_tile(x, y, p.tile, p.facing, Some(Color::Yellow));
+    }
+    for (bidx, trail) in board.players.iter().enumerate() {
+        let pos = trail.last().unwrap();
+        let (x, y) = port_point(pos.row, pos.col, pos.port);
+        let ch = if pos.alive { '@' } else { 'x' };
+        let color = PLAYER_COLORS[bidx % PLAYER_COLORS.len()];
+        canvas.put(x as usize, y as usize, ch, Some(color));
+    }
+    canvas
+}
+
+// Draws the tiles in a hand side by side, highlighting the selected one.
+pub fn draw_hand(
+    hand: &[Tile],
+    facings: &[Direction],
+    selected: usize,
+) -> Canvas {
+    let step = CELL_WIDTH + 2;
+    let mut canvas = Canvas::new(hand.len() * step, CELL_HEIGHT + 2);
+    for (i, tile) in hand.iter().enumerate() {
+        let color = (i == selected).then_some(Color::Yellow);
+        canvas.draw_box(i * step, 0, color);
+        canvas.draw_tile(i * step, 0, tile, facings[i], color);
+        let label = char::from(b'1' + i as u8);
+        canvas.put(i * step + CELL_WIDTH / 2, CELL_HEIGHT + 1, label, color);
+    }
+    canvas
+}
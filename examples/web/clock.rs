@@ -0,0 +1,153 @@
+use crate::lobby::GameSettings;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// Keeps time for a game with time limits. Each turn has its own limit, and
+// each player has a bank of time for the whole game; whichever runs out
+// first ends the turn.
+pub struct Clock {
+    turn_limit: Option<Duration>,
+    game_limit: Option<Duration>,
+    // Time left for the rest of the game, by username. Players who haven't
+    // had a turn yet still have the whole game limit.
+    banks: HashMap<String, Duration>,
+    // Whose clock is running, and since when
+    current: String,
+    turn_started: Instant,
+}
+
+// What players see of the clock.
+#[derive(Serialize)]
+pub struct ClockView {
+    pub player: String,
+    // Milliseconds until the current player has to move
+    pub turn_remaining_ms: u64,
+    // Milliseconds left for the rest of the game, by username
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub game_remaining_ms: HashMap<String, u64>,
+}
+
+fn seconds(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+impl Clock {
+    // Starts the clock for the first player, or returns None if the game has
+    // no time limits.
+    pub fn new(
+        settings: &GameSettings,
+        username: &str,
+        now: Instant,
+    ) -> Option<Self> {
+        let turn_limit = seconds(settings.turn_seconds);
+        let game_limit = seconds(settings.game_seconds);
+        if turn_limit.is_none() && game_limit.is_none() {
+            return None;
+        }
+        Some(Self {
+            turn_limit,
+            game_limit,
+            banks: HashMap::new(),
+            current: username.to_owned(),
+            turn_started: now,
+        })
+    }
+
+    pub fn current_player(&self) -> &str {
+        &self.current
+    }
+
+    fn bank(&self, username: &str) -> Option<Duration> {
+        let limit = self.game_limit?;
+        Some(self.banks.get(username).copied().unwrap_or(limit))
+    }
+
+    // Stops the running clock, charging the time used to that player, and
+    // starts a new turn for `username`, who may be the same player again.
+    pub fn start_turn(&mut self, username: &str, now: Instant) {
+        if let Some(bank) = self.bank(&self.current) {
+            let used = now.saturating_duration_since(self.turn_started);
+            self.banks
+                .insert(self.current.clone(), bank.saturating_sub(used));
+        }
+        self.current = username.to_owned();
+        self.turn_started = now;
+    }
+
+    // When the current player runs out of time.
+    pub fn deadline(&self) -> Instant {
+        let allowed = [self.turn_limit, self.bank(&self.current)]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or_default();
+        self.turn_started + allowed
+    }
+
+    pub fn view(&self, now: Instant) -> ClockView {
+        let used = now.saturating_duration_since(self.turn_started);
+        let mut game_remaining_ms: HashMap<String, u64> = self
+            .banks
+            .iter()
+            .map(|(name, bank)| (name.clone(), bank.as_millis() as u64))
+            .collect();
+        if let Some(bank) = self.bank(&self.current) {
+            let left = bank.saturating_sub(used).as_millis() as u64;
+            game_remaining_ms.insert(self.current.clone(), left);
+        }
+        ClockView {
+            player: self.current.clone(),
+            turn_remaining_ms: self
+                .deadline()
+                .saturating_duration_since(now)
+                .as_millis() as u64,
+            game_remaining_ms,
+        }
+    }
+}
+
+#[test]
+fn test_clock() {
+    let settings = GameSettings {
+        turn_seconds: 30,
+        game_seconds: 50,
+        ..Default::default()
+    };
+    let start = Instant::now();
+    let mut clock = Clock::new(&settings, "Bob", start).unwrap();
+    assert_eq!(clock.deadline(), start + Duration::from_secs(30));
+
+    let later = start + Duration::from_secs(20);
+    clock.start_turn("Ann", later);
+    clock.start_turn("Bob", later + Duration::from_secs(5));
+    // Bob only has 30 seconds left in the game.
+    let now = later + Duration::from_secs(5);
+    assert_eq!(clock.deadline(), now + Duration::from_secs(30));
+    clock.start_turn("Ann", now + Duration::from_secs(25));
+    clock.start_turn("Bob", now + Duration::from_secs(25));
+    let now = now + Duration::from_secs(25);
+    assert_eq!(clock.deadline(), now + Duration::from_secs(5));
+
+    let view = clock.view(now);
+    assert_eq!(view.player, "Bob");
+    assert_eq!(view.turn_remaining_ms, 5000);
+    assert_eq!(view.game_remaining_ms["Ann"], 45000);
+
+    assert!(Clock::new(&GameSettings::default(), "Bob", start).is_none());
+}
+
+#[test]
+fn test_consecutive_turns() {
+    // A lone survivor keeps taking turns, and each one gets the full limit.
+    let settings = GameSettings {
+        turn_seconds: 30,
+        ..Default::default()
+    };
+    let start = Instant::now();
+    let mut clock = Clock::new(&settings, "Bob", start).unwrap();
+    let next = start + Duration::from_secs(20);
+    clock.start_turn("Bob", next);
+    assert_eq!(clock.deadline(), next + Duration::from_secs(30));
+    assert_eq!(clock.view(next).turn_remaining_ms, 30000);
+}
@@ -1,8 +1,20 @@
 use rand::distr::{Distribution, Uniform};
 use serde::{Deserialize, Serialize};
 use strecke::board;
+use strecke::rules::{Rules, Scoring};
+#[cfg(test)]
+use strecke::{board::edge_position, tiles::Port};
 
 const MAX_PLAYERS: usize = 11;
+// Longest time limit the host can set, in seconds.
+const MAX_CLOCK_SECONDS: u64 = 24 * 60 * 60;
+// Largest bonus for surviving under longest-path scoring
+const MAX_SURVIVAL_BONUS: usize = 100;
+// Team games are always two evenly matched teams against each other.
+pub const NUM_TEAMS: usize = 2;
+// AI players fill the empty seats and are named for it, so no one can sign up
+// with a name like theirs.
+pub const AI_NAME_PREFIX: &str = "AI player #";
 // No I,O
 static CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
 
@@ -16,14 +28,63 @@ pub fn generate_lobby_code() -> String {
         .collect()
 }
 
-#[derive(Serialize, Deserialize)]
+// Options chosen by the host that apply to the whole game.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameSettings {
+    // Whether players can ask for suggested moves
+    pub allow_hints: bool,
+    // Whether teammates can see each other's tiles
+    pub open_team_hands: bool,
+    // Whether everyone can see everyone's tiles
+    pub open_hands: bool,
+    // Whether everyone can see the order of the tile stack
+    pub public_stack: bool,
+    // Whether players draft their starting tiles
+    pub draft: bool,
+    // Whether power tiles are mixed into the stack
+    pub power_tiles: bool,
+    // Seconds allowed for each turn, or zero for no limit
+    pub turn_seconds: u64,
+    // Seconds each player has for the whole game, or zero for no limit
+    pub game_seconds: u64,
+    // Whether running out of time knocks a player out, rather than making a
+    // safe move for them
+    pub eliminate_on_timeout: bool,
+    // How many edge positions apart starting seats must be
+    pub start_spacing: board::EdgePos,
+    // How the winner is decided
+    pub scoring: Scoring,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            allow_hints: true,
+            open_team_hands: false,
+            open_hands: false,
+            public_stack: false,
+            draft: false,
+            power_tiles: false,
+            turn_seconds: 0,
+            game_seconds: 0,
+            eliminate_on_timeout: false,
+            start_spacing: board::MIN_START_SPACING,
+            scoring: Scoring::LastStanding,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Lobby {
     // Usernames of the present players
     names: Vec<String>,
     // Parallel vector of starting positions
     start_positions: Vec<board::EdgePos>,
+    // Parallel vector of teams, if the players have picked any
+    teams: Vec<Option<usize>>,
     // Total number of players to allow
     max_num_players: usize,
+    settings: GameSettings,
 }
 
 impl Lobby {
@@ -36,7 +97,9 @@ impl Lobby {
         Lobby {
             names,
             start_positions,
+            teams: vec![None],
             max_num_players,
+            settings: GameSettings::default(),
         }
     }
 
@@ -44,6 +107,130 @@ impl Lobby {
         &self.names[0]
     }
 
+    pub fn settings(&self) -> &GameSettings {
+        &self.settings
+    }
+
+    pub fn set_allow_hints(&mut self, allow: bool) {
+        self.settings.allow_hints = allow;
+    }
+
+    // Turns one of the rule options on or off, by name.
+    pub fn set_option(
+        &mut self,
+        option: &str,
+        value: bool,
+    ) -> Result<(), &str> {
+        let setting = match option {
+            "open_team_hands" => &mut self.settings.open_team_hands,
+            "open_hands" => &mut self.settings.open_hands,
+            "public_stack" => &mut self.settings.public_stack,
+            "draft" => &mut self.settings.draft,
+            "power_tiles" => &mut self.settings.power_tiles,
+            "eliminate_on_timeout" => &mut self.settings.eliminate_on_timeout,
+            _ => return Err("No such option"),
+        };
+        *setting = value;
+        Ok(())
+    }
+
+    // Sets the turn and game time limits, in seconds. Zero means no limit.
+    pub fn set_time_limits(
+        &mut self,
+        turn_seconds: u64,
+        game_seconds: u64,
+    ) -> Result<(), &str> {
+        if turn_seconds > MAX_CLOCK_SECONDS || game_seconds > MAX_CLOCK_SECONDS
+        {
+            return Err("Time limit is too long");
+        }
+        self.settings.turn_seconds = turn_seconds;
+        self.settings.game_seconds = game_seconds;
+        Ok(())
+    }
+
+    // Sets how far apart starting seats must be.
+    pub fn set_start_spacing(
+        &mut self,
+        spacing: board::EdgePos,
+    ) -> Result<(), &str> {
+        if !(0..=board::MAX_START_SPACING).contains(&spacing) {
+            return Err("Invalid seat spacing");
+        }
+        self.settings.start_spacing = spacing;
+        Ok(())
+    }
+
+    pub fn set_scoring(&mut self, scoring: Scoring) -> Result<(), &str> {
+        if let Scoring::LongestPath { survival_bonus } = scoring
+            && survival_bonus > MAX_SURVIVAL_BONUS
+        {
+            return Err("Survival bonus is too big");
+        }
+        self.settings.scoring = scoring;
+        Ok(())
+    }
+
+    // Whether any two seated players are closer than the spacing allows.
+    fn seats_too_close(&self) -> bool {
+        let seated: Vec<board::EdgePos> = self
+            .start_positions
+            .iter()
+            .copied()
+            .filter(|&pos| pos < board::NOT_READY)
+            .collect();
+        seated.iter().enumerate().any(|(i, &a)| {
+            seated[..i].iter().any(|&b| {
+                board::edge_distance(a, b) < self.settings.start_spacing
+            })
+        })
+    }
+
+    // Puts a player on a team, or takes them off it if `team` is NUM_TEAMS.
+    pub fn set_team(
+        &mut self,
+        team: usize,
+        username: &str,
+    ) -> Result<(), &str> {
+        if team > NUM_TEAMS {
+            return Err("Invalid team");
+        }
+        let i = self
+            .names
+            .iter()
+            .position(|name| name == username)
+            .ok_or("Not in this lobby")?;
+        self.teams[i] = (team < NUM_TEAMS).then_some(team);
+        Ok(())
+    }
+
+    fn is_team_game(&self) -> bool {
+        self.teams.iter().any(|team| team.is_some())
+    }
+
+    // The rules for a game with the seated players.
+    pub fn rules(&self) -> Rules {
+        let teams = if self.is_team_game() {
+            self.teams
+                .iter()
+                .zip(self.start_positions.iter())
+                .filter(|(_, pos)| **pos < board::NOT_READY)
+                .map(|(team, _)| team.unwrap_or(0))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Rules {
+            teams,
+            open_team_hands: self.settings.open_team_hands,
+            open_hands: self.settings.open_hands,
+            public_stack: self.settings.public_stack,
+            draft: self.settings.draft,
+            power_tiles: self.settings.power_tiles,
+            scoring: self.settings.scoring,
+        }
+    }
+
     pub fn resize(&mut self, new_size: usize) -> Result<(), &str> {
         if new_size > MAX_PLAYERS {
             return Err("Too many players");
@@ -52,6 +239,7 @@ impl Lobby {
         if self.names.len() > new_size {
             self.names.truncate(new_size);
             self.start_positions.truncate(new_size);
+            self.teams.truncate(new_size);
         }
         Ok(())
     }
@@ -64,11 +252,24 @@ impl Lobby {
         if !board::is_valid_edge_position(seat_idx) {
             return Err("Invalid seat_idx");
         }
-        if let Some(i) = self.names.iter().position(|name| name == &username) {
+        let me = self.names.iter().position(|name| name == &username);
+        let too_close =
+            self.start_positions.iter().enumerate().any(|(i, &pos)| {
+                Some(i) != me
+                    && pos < board::NOT_READY
+                    && seat_idx < board::NOT_READY
+                    && board::edge_distance(pos, seat_idx)
+                        < self.settings.start_spacing
+            });
+        if too_close {
+            return Err("Too close to another player");
+        }
+        if let Some(i) = me {
             self.start_positions[i] = seat_idx;
         } else {
             self.names.push(username);
             self.start_positions.push(seat_idx);
+            self.teams.push(None);
         }
         Ok(())
     }
@@ -87,7 +288,7 @@ impl Lobby {
     ) -> impl std::iter::Iterator<Item = (String, board::Position)> {
         self.names
             .into_iter()
-            .zip(self.start_positions.into_iter())
+            .zip(self.start_positions)
             .filter(|(_, pos)| *pos < board::NOT_READY)
             .map(|(name, pos)| (name, board::edge_position(pos)))
     }
@@ -99,13 +300,67 @@ impl Lobby {
         if username != self.host() {
             return Err("Only the host can start the game");
         }
+        if self.seats_too_close() {
+            return Err("Some players are seated too close together");
+        }
+        // AI players have to fit in around the humans.
+        if self.ai_seats(&mut rand::rng()).is_none() {
+            return Err("Not enough room for everyone at this seat spacing");
+        }
         if !self.start_positions.iter().any(|&x| x < board::NOT_READY) {
             return Err("No human players are ready to play");
         }
+        let seated_teams = self
+            .teams
+            .iter()
+            .zip(self.start_positions.iter())
+            .filter(|(_, pos)| **pos < board::NOT_READY);
+        if self.is_team_game()
+            && seated_teams.clone().any(|(team, _)| team.is_none())
+        {
+            return Err("Everyone needs to pick a team");
+        }
+        if !self.is_team_game() {
+            return Ok(());
+        }
+        // Teams are evenly matched, 2v2 or 3v3 and so on. AI players fill
+        // any empty seats, so they can make up the numbers.
+        let team_size = self.max_num_players / NUM_TEAMS;
+        if team_size < 2 || !self.max_num_players.is_multiple_of(NUM_TEAMS) {
+            return Err("Team games need an even split, like 2v2 or 3v3");
+        }
+        let overfull = (0..NUM_TEAMS).any(|t| {
+            seated_teams
+                .clone()
+                .filter(|(team, _)| **team == Some(t))
+                .count()
+                > team_size
+        });
+        if overfull {
+            return Err("Teams need the same number of players");
+        }
         Ok(())
     }
 
-    pub fn prepare_for_game(&mut self) {
+    // Seats for the AI players who fill up the lobby, if they fit.
+    fn ai_seats(
+        &self,
+        rng: &mut impl rand::Rng,
+    ) -> Option<Vec<board::EdgePos>> {
+        let humans: Vec<board::EdgePos> = self
+            .start_positions
+            .iter()
+            .copied()
+            .filter(|&pos| pos < board::NOT_READY)
+            .collect();
+        let num_ai = self.max_num_players.checked_sub(humans.len())?;
+        board::spread_seats(&humans, num_ai, self.settings.start_spacing, rng)
+    }
+
+    pub fn prepare_for_game(&mut self) -> Result<(), &'static str> {
+        let seats = self
+            .ai_seats(&mut rand::rng())
+            .ok_or("Not enough room for everyone at this seat spacing")?;
         let mut indices: Vec<usize> = (0..self.start_positions.len()).collect();
         indices.sort_by_key(|&i| &self.start_positions[i]);
         apply_permutation(indices.as_mut_slice(), self.names.as_mut_slice());
@@ -113,6 +368,7 @@ impl Lobby {
             indices.as_mut_slice(),
             self.start_positions.as_mut_slice(),
         );
+        apply_permutation(indices.as_mut_slice(), self.teams.as_mut_slice());
         let num_humans = 1 + self
             .start_positions
             .iter()
@@ -122,37 +378,35 @@ impl Lobby {
         if num_humans < self.max_num_players {
             self.names.truncate(num_humans);
             self.start_positions.truncate(num_humans);
-            let range = Uniform::try_from(0..48).unwrap();
-            let mut rng = rand::rng();
-            for i in 0..(self.max_num_players - num_humans) {
-                self.names.push(format!("AI player #{}", i + 1));
-                // Assign a random starting location that isn't in use.
-                // TODO: enforce separation constraints
-                loop {
-                    let pos = range.sample(&mut rng);
-                    if !self.start_positions.contains(&pos) {
-                        self.start_positions.push(pos);
-                        break;
-                    }
-                }
+            self.teams.truncate(num_humans);
+            let team_game = self.is_team_game();
+            for (i, seat) in seats.into_iter().enumerate() {
+                self.names.push(format!("{}{}", AI_NAME_PREFIX, i + 1));
+                // AI players even out the teams.
+                let team = (0..NUM_TEAMS).min_by_key(|&t| {
+                    self.teams.iter().filter(|&&x| x == Some(t)).count()
+                });
+                self.teams.push(team.filter(|_| team_game));
+                self.start_positions.push(seat);
             }
         }
+        Ok(())
     }
 }
 
 #[inline(always)]
 fn toggle_mark_idx(idx: usize) -> usize {
-    idx ^ isize::min_value() as usize
+    idx ^ isize::MIN as usize
 }
 
 #[inline(always)]
 fn idx_is_marked(idx: usize) -> bool {
-    (idx & (isize::min_value() as usize)) != 0
+    (idx & (isize::MIN as usize)) != 0
 }
 
 fn apply_permutation<T>(indices: &mut [usize], slice: &mut [T]) {
     assert_eq!(slice.len(), indices.len());
-    assert!(slice.len() <= isize::max_value() as usize);
+    assert!(slice.len() <= isize::MAX as usize);
     for i in 0..indices.len() {
         let i_idx = indices[i];
         if idx_is_marked(i_idx) {
@@ -186,6 +440,135 @@ fn test_make_code() {
     );
 }
 
+#[test]
+fn test_team_lobby() {
+    let mut x = Lobby::new("Bob".to_owned());
+    x.take_seat(30, "Bob".to_owned()).unwrap();
+    x.take_seat(10, "Ann".to_owned()).unwrap();
+    x.set_team(0, "Bob").unwrap();
+    assert!(x.set_team(0, "Cat").is_err());
+    assert_eq!(
+        x.run_pregame_checks("Bob"),
+        Err("Everyone needs to pick a team")
+    );
+    x.set_team(0, "Ann").unwrap();
+    assert_eq!(
+        x.run_pregame_checks("Bob"),
+        Err("Team games need an even split, like 2v2 or 3v3")
+    );
+    x.resize(5).unwrap();
+    assert!(x.run_pregame_checks("Bob").is_err());
+    // No 3v1.
+    x.resize(4).unwrap();
+    x.take_seat(20, "Cat".to_owned()).unwrap();
+    x.set_team(0, "Cat").unwrap();
+    assert_eq!(
+        x.run_pregame_checks("Bob"),
+        Err("Teams need the same number of players")
+    );
+    x.set_team(1, "Cat").unwrap();
+    // AI players even out the teams.
+    assert!(x.run_pregame_checks("Bob").is_ok());
+    x.prepare_for_game().unwrap();
+    // In seat order: Ann, Cat, Bob and then the AI player.
+    let rules = x.rules();
+    assert_eq!(rules.teams, [0, 1, 0, 1]);
+    assert!(rules.validate(4).is_ok());
+}
+
+#[test]
+fn test_seat_spacing() {
+    let mut x = Lobby::new("Bob".to_owned());
+    x.take_seat(10, "Bob".to_owned()).unwrap();
+    assert!(x.take_seat(11, "Ann".to_owned()).is_err());
+    assert!(x.take_seat(10, "Ann".to_owned()).is_err());
+    // Moving next to your own old seat is fine.
+    x.take_seat(11, "Bob".to_owned()).unwrap();
+    x.take_seat(20, "Ann".to_owned()).unwrap();
+    x.resize(6).unwrap();
+    x.prepare_for_game().unwrap();
+    let seats = &x.start_positions;
+    assert_eq!(seats.len(), 6);
+    for (i, &a) in seats.iter().enumerate() {
+        for &b in &seats[..i] {
+            assert!(board::edge_distance(a, b) >= board::MIN_START_SPACING);
+        }
+    }
+}
+
+#[test]
+fn test_custom_seat_spacing() {
+    let mut x = Lobby::new("Bob".to_owned());
+    x.take_seat(10, "Bob".to_owned()).unwrap();
+    x.take_seat(14, "Ann".to_owned()).unwrap();
+    assert!(x.set_start_spacing(board::MAX_START_SPACING + 1).is_err());
+    x.set_start_spacing(6).unwrap();
+    assert!(x.take_seat(18, "Cat".to_owned()).is_err());
+    assert_eq!(
+        x.run_pregame_checks("Bob"),
+        Err("Some players are seated too close together")
+    );
+    x.take_seat(40, "Ann".to_owned()).unwrap();
+    x.resize(9).unwrap();
+    assert_eq!(
+        x.run_pregame_checks("Bob"),
+        Err("Not enough room for everyone at this seat spacing")
+    );
+    x.resize(4).unwrap();
+    assert!(x.run_pregame_checks("Bob").is_ok());
+}
+
+#[test]
+fn test_uneven_seat_spacing() {
+    // Four players would fit a quarter of the way round from each other, but
+    // not with the humans seated like this.
+    let mut x = Lobby::new("Bob".to_owned());
+    x.set_start_spacing(12).unwrap();
+    x.take_seat(0, "Bob".to_owned()).unwrap();
+    x.take_seat(18, "Ann".to_owned()).unwrap();
+    x.resize(4).unwrap();
+    let no_room = Err("Not enough room for everyone at this seat spacing");
+    assert_eq!(x.run_pregame_checks("Bob"), no_room);
+    assert_eq!(x.clone().prepare_for_game(), no_room);
+    x.resize(3).unwrap();
+    assert!(x.run_pregame_checks("Bob").is_ok());
+    x.prepare_for_game().unwrap();
+    let seats = &x.start_positions;
+    assert_eq!(seats.len(), 3);
+    assert!(seats[2] >= 30 && seats[2] <= 36);
+}
+
+#[test]
+fn test_lobby_options() {
+    let mut x = Lobby::new("Bob".to_owned());
+    x.set_option("open_hands", true).unwrap();
+    x.set_option("public_stack", true).unwrap();
+    assert!(x.set_option("see_the_future", true).is_err());
+    let rules = x.rules();
+    assert!(rules.open_hands && rules.public_stack);
+    assert!(rules.teams.is_empty());
+    assert_eq!(rules.scoring, Scoring::LastStanding);
+
+    let longest = Scoring::LongestPath { survival_bonus: 5 };
+    x.set_scoring(longest).unwrap();
+    assert_eq!(x.rules().scoring, longest);
+    let too_big = Scoring::LongestPath {
+        survival_bonus: MAX_SURVIVAL_BONUS + 1,
+    };
+    assert!(x.set_scoring(too_big).is_err());
+}
+
+#[test]
+fn test_time_limits() {
+    let mut x = Lobby::new("Bob".to_owned());
+    x.set_time_limits(30, 600).unwrap();
+    x.set_option("eliminate_on_timeout", true).unwrap();
+    assert!(x.set_time_limits(30, MAX_CLOCK_SECONDS + 1).is_err());
+    let settings = x.settings();
+    assert_eq!((settings.turn_seconds, settings.game_seconds), (30, 600));
+    assert!(settings.eliminate_on_timeout);
+}
+
 #[test]
 fn test_solo_lobby() {
     let x = Lobby::new("Bob".to_owned());
@@ -1,8 +1,13 @@
+mod clock;
 mod lobby;
 mod settings;
 mod webapp;
+use chrono::{Datelike, Utc};
 use futures::{SinkExt, StreamExt, TryFutureExt};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use strecke::rules::Scoring;
+use strecke::{agent, puzzle};
 use tokio::sync::{Mutex, mpsc};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use warp::Filter;
@@ -16,6 +21,9 @@ extern crate log;
 
 type Database = Arc<Mutex<webapp::AppState>>;
 
+// How often the server checks for players who have run out of time.
+const CLOCK_TICK: Duration = Duration::from_secs(1);
+
 lazy_static! {
     static ref CONFIG: settings::Settings = settings::Settings::new().unwrap();
 }
@@ -27,6 +35,7 @@ async fn main() {
 
     let app = webapp::AppState::new(&CONFIG.db.name).unwrap();
     let db: Database = Arc::new(Mutex::new(app));
+    tokio::task::spawn(enforce_time_limits(Arc::clone(&db)));
     let db_getter = warp::any().map(move || Arc::clone(&db));
     let needs_cookie = warp::cookie(&CONFIG.cookie.name).and_then(
         move |token: String| async move {
@@ -85,6 +94,18 @@ async fn main() {
         .and(needs_cookie)
         .and_then(do_new_game);
 
+    // POST /new_puzzle => redirect to the game
+    let new_puzzle = warp::path("new_puzzle")
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(do_new_puzzle);
+
+    // GET /puzzle/$game_id => JSON
+    let puzzle = warp::path!("puzzle" / i64)
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(get_puzzle_json);
+
     // GET /board/$game_id => JSON
     let board = warp::path!("board" / i64)
         .and(db_getter.clone())
@@ -97,6 +118,18 @@ async fn main() {
         .and(needs_cookie)
         .and_then(get_hand_json);
 
+    // GET /rules/$game_id => JSON
+    let rules = warp::path!("rules" / i64)
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(get_rules_json);
+
+    // GET /hint/$game_id => JSON
+    let hint = warp::path!("hint" / i64)
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(get_hint_json);
+
     // GET /lobby/$code => JSON
     let lobby_data = warp::path!("lobby_data" / String)
         .and(db_getter.clone())
@@ -110,6 +143,19 @@ async fn main() {
         .and(needs_cookie)
         .and_then(play_tile);
 
+    // POST /draft => OK
+    let draft = warp::path("draft")
+        .and(warp::body::json())
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(draft_tile);
+
+    // POST /resign/$game_id => OK
+    let resign = warp::path!("resign" / i64)
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(resign_game);
+
     // POST /lobby_seat/$code/$seat_idx
     let lobby_seat = warp::path!("lobby_seat" / String / i8)
         .and(db_getter.clone())
@@ -122,6 +168,43 @@ async fn main() {
         .and(needs_cookie)
         .and_then(resize_lobby);
 
+    // POST /lobby_hints/$code/$allow
+    let lobby_hints = warp::path!("lobby_hints" / String / bool)
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(set_lobby_hints);
+
+    // POST /lobby_team/$code/$team
+    let lobby_team = warp::path!("lobby_team" / String / usize)
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(set_lobby_team);
+
+    // POST /lobby_option/$code/$option/$value
+    let lobby_option = warp::path!("lobby_option" / String / String / bool)
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(set_lobby_option);
+
+    // POST /lobby_clock/$code/$turn_seconds/$game_seconds
+    let lobby_clock = warp::path!("lobby_clock" / String / u64 / u64)
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(set_lobby_clock);
+
+    // POST /lobby_spacing/$code/$spacing
+    let lobby_spacing = warp::path!("lobby_spacing" / String / i8)
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(set_lobby_spacing);
+
+    // POST /lobby_scoring/$code => OK
+    let lobby_scoring = warp::path!("lobby_scoring" / String)
+        .and(warp::body::json())
+        .and(db_getter.clone())
+        .and(needs_cookie)
+        .and_then(set_lobby_scoring);
+
     // GET /ws => websocket
     let ws = warp::path!("ws" / String)
         .and(warp::ws())
@@ -140,17 +223,29 @@ async fn main() {
             .or(static_files)
             .or(board)
             .or(hand)
+            .or(hint)
+            .or(rules)
+            .or(puzzle)
             .or(lobby_data)
             .or(check_login),
     );
     let posts = warp::post().and(
-        play.or(lobby_seat)
+        play.or(draft)
+            .or(resign)
+            .or(lobby_seat)
             .or(lobby_size)
+            .or(lobby_hints)
+            .or(lobby_team)
+            .or(lobby_option)
+            .or(lobby_clock)
+            .or(lobby_spacing)
+            .or(lobby_scoring)
             .or(login)
             .or(register)
             .or(logout)
             .or(new_lobby)
-            .or(new_game),
+            .or(new_game)
+            .or(new_puzzle),
     );
 
     let routes = gets.or(posts).or(ws);
@@ -238,6 +333,59 @@ async fn do_new_lobby(
         .body("".to_owned()))
 }
 
+// Picks today's puzzle from the configured file.
+fn daily_puzzle() -> Result<puzzle::Puzzle, String> {
+    let text = std::fs::read_to_string(&CONFIG.puzzles.file)
+        .map_err(|e| e.to_string())?;
+    let lines: Vec<&str> = text.lines().filter(|l| !l.is_empty()).collect();
+    if lines.is_empty() {
+        return Err("No puzzles available".to_owned());
+    }
+    let day = Utc::now().num_days_from_ce() as usize;
+    serde_json::from_str(lines[day % lines.len()]).map_err(|e| e.to_string())
+}
+
+async fn do_new_puzzle(
+    db: Database,
+    username: String,
+) -> WarpResult<impl warp::Reply> {
+    let result = match daily_puzzle() {
+        Ok(puzzle) => db
+            .lock()
+            .await
+            .new_puzzle_game(&puzzle, username.clone())
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+    Ok(match result {
+        Ok(game_id) => {
+            info!("Started puzzle game {} for user {}", game_id, &username);
+            Response::builder()
+                .status(StatusCode::SEE_OTHER)
+                .header(header::LOCATION, format!("/game?id={}", game_id))
+                .body("".to_owned())
+        }
+        Err(e) => {
+            error!("Failed to start puzzle: {}", e);
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(e)
+        }
+    })
+}
+
+async fn get_puzzle_json(
+    game_id: i64,
+    db: Database,
+    _username: String,
+) -> WarpResult<impl warp::Reply> {
+    let app = db.lock().await;
+    Ok(match app.game(game_id).and_then(|game| game.puzzle()) {
+        Some(puzzle) => warp::reply::json(&puzzle.objective),
+        None => warp::reply::json(&"Not a puzzle."),
+    })
+}
+
 async fn get_board_json(
     game_id: i64,
     db: Database,
@@ -258,13 +406,43 @@ async fn get_hand_json(
     let app = db.lock().await;
     Ok(match app.game(game_id) {
         Some(game) => match game.get_player(&username) {
-            Some(p) => warp::reply::json(p),
+            Some(player) => warp::reply::json(&webapp::HandView {
+                player,
+                other_hands: game.visible_hands(&username),
+                upcoming_tiles: game.upcoming_tiles(),
+                draft_pool: game.draft_pool(),
+                clock: app.clock_view(game_id),
+            }),
             None => warp::reply::json(&"Player not found."),
         },
         None => warp::reply::json(&"Game not found."),
     })
 }
 
+async fn get_rules_json(
+    game_id: i64,
+    db: Database,
+    _username: String,
+) -> WarpResult<impl warp::Reply> {
+    let app = db.lock().await;
+    Ok(match app.game(game_id) {
+        Some(game) => warp::reply::json(game.rules()),
+        None => warp::reply::json(&"Game not found."),
+    })
+}
+
+async fn get_hint_json(
+    game_id: i64,
+    db: Database,
+    username: String,
+) -> WarpResult<impl warp::Reply> {
+    let app = db.lock().await;
+    Ok(match app.hint(game_id, &username) {
+        Ok(suggestion) => warp::reply::json(&suggestion),
+        Err(e) => warp::reply::json(&e.to_string()),
+    })
+}
+
 async fn get_lobby_json(
     lobby_code: String,
     db: Database,
@@ -282,10 +460,85 @@ async fn play_tile(
     db: Database,
     username: String,
 ) -> WarpResult<impl warp::Reply> {
+    let game_id = params.game_id;
     db.lock().await.take_turn(params, &username);
+    run_ai_turns(game_id, db).await;
+    Ok("OK")
+}
+
+async fn draft_tile(
+    params: webapp::DraftParams,
+    db: Database,
+    username: String,
+) -> WarpResult<impl warp::Reply> {
+    let game_id = params.game_id;
+    db.lock().await.draft_tile(params, &username);
+    run_ai_turns(game_id, db).await;
     Ok("OK")
 }
 
+async fn resign_game(
+    game_id: i64,
+    db: Database,
+    username: String,
+) -> WarpResult<impl warp::Reply> {
+    db.lock().await.resign(game_id, &username);
+    run_ai_turns(game_id, db).await;
+    Ok("OK")
+}
+
+// Lets AI players move until it's a human's turn again. The agents think on a
+// blocking task, so the server isn't locked up while they do. Only one of
+// these runs per game, so a choice is never made from a stale position.
+async fn run_ai_turns(game_id: i64, db: Database) {
+    if !db.lock().await.claim_ai_turns(game_id) {
+        return;
+    }
+    loop {
+        let Some((ai_name, obs)) = db.lock().await.next_ai_turn(game_id) else {
+            return;
+        };
+        let level = CONFIG.ai.level;
+        // Draft picks are quick to choose, so they skip the blocking task.
+        if !obs.draft_pool.is_empty() {
+            let pick = agent::create_agent(level).choose_draft_pick(&obs);
+            if !db.lock().await.take_ai_draft(game_id, &ai_name, pick) {
+                break;
+            }
+            continue;
+        }
+        let deadline = Instant::now() + CONFIG.ai.time_budget();
+        let choice = tokio::task::spawn_blocking(move || {
+            agent::create_agent(level).choose_action(&obs, deadline)
+        })
+        .await;
+        let choice = match choice {
+            Ok(choice) => Some(choice),
+            Err(e) => {
+                error!("AI task failed for {}: {}", &ai_name, e);
+                None
+            }
+        };
+        if !db.lock().await.take_ai_turn(game_id, &ai_name, choice) {
+            break;
+        }
+    }
+    db.lock().await.release_ai_turns(game_id);
+}
+
+// Moves for players who run out of time, then lets any AI players after them
+// take their turns.
+async fn enforce_time_limits(db: Database) {
+    let mut interval = tokio::time::interval(CLOCK_TICK);
+    loop {
+        interval.tick().await;
+        let game_ids = db.lock().await.handle_timeouts(Instant::now());
+        for game_id in game_ids {
+            tokio::task::spawn(run_ai_turns(game_id, Arc::clone(&db)));
+        }
+    }
+}
+
 async fn take_seat(
     lobby_code: String,
     seat_idx: i8,
@@ -306,6 +559,81 @@ async fn resize_lobby(
     Ok("OK")
 }
 
+async fn set_lobby_hints(
+    lobby_code: String,
+    allow: bool,
+    db: Database,
+    username: String,
+) -> WarpResult<impl warp::Reply> {
+    db.lock()
+        .await
+        .set_lobby_hints(&lobby_code, allow, &username);
+    Ok("OK")
+}
+
+async fn set_lobby_team(
+    lobby_code: String,
+    team: usize,
+    db: Database,
+    username: String,
+) -> WarpResult<impl warp::Reply> {
+    db.lock().await.set_lobby_team(&lobby_code, team, &username);
+    Ok("OK")
+}
+
+async fn set_lobby_option(
+    lobby_code: String,
+    option: String,
+    value: bool,
+    db: Database,
+    username: String,
+) -> WarpResult<impl warp::Reply> {
+    db.lock()
+        .await
+        .set_lobby_option(&lobby_code, &option, value, &username);
+    Ok("OK")
+}
+
+async fn set_lobby_clock(
+    lobby_code: String,
+    turn_seconds: u64,
+    game_seconds: u64,
+    db: Database,
+    username: String,
+) -> WarpResult<impl warp::Reply> {
+    db.lock().await.set_lobby_clock(
+        &lobby_code,
+        turn_seconds,
+        game_seconds,
+        &username,
+    );
+    Ok("OK")
+}
+
+async fn set_lobby_spacing(
+    lobby_code: String,
+    spacing: i8,
+    db: Database,
+    username: String,
+) -> WarpResult<impl warp::Reply> {
+    db.lock()
+        .await
+        .set_lobby_spacing(&lobby_code, spacing, &username);
+    Ok("OK")
+}
+
+async fn set_lobby_scoring(
+    lobby_code: String,
+    scoring: Scoring,
+    db: Database,
+    username: String,
+) -> WarpResult<impl warp::Reply> {
+    db.lock()
+        .await
+        .set_lobby_scoring(&lobby_code, scoring, &username);
+    Ok("OK")
+}
+
 async fn new_connection(
     ws: WebSocket,
     db: Database,
@@ -1,6 +1,7 @@
 use config::{Config, ConfigError, Environment, File};
 use jsonwebtoken::{DecodingKey, EncodingKey};
 use serde::Deserialize;
+use std::time::Duration;
 
 #[derive(Clone)]
 struct KeyHolder {
@@ -48,11 +49,32 @@ pub struct Database {
     pub name: String,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct Ai {
+    pub level: usize,
+    // Milliseconds per move.
+    pub budget: u64,
+}
+
+impl Ai {
+    pub fn time_budget(&self) -> Duration {
+        Duration::from_millis(self.budget)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Puzzles {
+    // JSON-lines file of puzzles, as written by the puzzles example.
+    pub file: String,
+}
+
 #[derive(Deserialize, Clone)]
 pub struct Settings {
     pub server: Server,
     pub cookie: Cookie,
     pub db: Database,
+    pub ai: Ai,
+    pub puzzles: Puzzles,
 }
 
 impl Settings {
@@ -1,16 +1,22 @@
+use crate::clock::{Clock, ClockView};
 use crate::lobby;
 use argon2::{self, Config};
 use chrono::Utc;
 use log::{error, info};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, hash_map::Entry};
+use std::collections::{HashMap, HashSet, hash_map::Entry};
 use std::error;
 use std::fmt;
+use std::time::Instant;
 use strecke::agent::{Agent, AvoidSuddenDeathAgent};
 use strecke::board;
-use strecke::game::GameManager;
-use strecke::tiles::Direction;
+use strecke::game::{
+    GameManager, GameResult, Player, PlayerObservation, Suggestion,
+};
+use strecke::puzzle::Puzzle;
+use strecke::rules::Scoring;
+use strecke::tiles::{Direction, Tile};
 
 #[derive(Deserialize)]
 pub struct UserCredentials {
@@ -21,9 +27,18 @@ pub struct UserCredentials {
 
 #[derive(Deserialize)]
 pub struct TurnParams {
-    game_id: i64,
+    pub game_id: i64,
     idx: usize,
     facing: Direction,
+    // Which neighbour to turn, when playing a spinner.
+    #[serde(default)]
+    spin: Option<Direction>,
+}
+
+#[derive(Deserialize)]
+pub struct DraftParams {
+    pub game_id: i64,
+    idx: usize,
 }
 
 #[derive(Serialize)]
@@ -34,33 +49,51 @@ pub enum LobbyResponse<'a> {
     Error { message: String },
 }
 
+// A player's own tiles, plus whatever else the rules let them see: other
+// players' tiles and the order of the stack.
+#[derive(Serialize)]
+pub struct HandView<'a> {
+    #[serde(flatten)]
+    pub player: &'a Player,
+    pub other_hands: Vec<&'a Player>,
+    pub upcoming_tiles: Option<Vec<Tile>>,
+    #[serde(skip_serializing_if = "<[Tile]>::is_empty")]
+    pub draft_pool: &'a [Tile],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clock: Option<ClockView>,
+}
+
 #[derive(Serialize)]
 #[serde(tag = "action")]
 pub enum TurnResponse<'a> {
     Update {
         board: &'a board::Board,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        clock: Option<ClockView>,
     },
     GameOver {
         board: &'a board::Board,
-        winner: String,
+        result: GameResult,
     },
     Error {
         message: String,
     },
 }
 
-enum GameStatus {
-    Ongoing,
-    Winner(String),
-    EveryoneLoses,
-}
-
 type WebsocketSender = tokio::sync::mpsc::UnboundedSender<warp::ws::Message>;
 
+// Strength of the agent that suggests moves to human players.
+const HINT_STRENGTH: usize = 2;
+
 pub struct AppState {
     games: HashMap<i64, GameManager>,
+    game_settings: HashMap<i64, lobby::GameSettings>,
     conn: rusqlite::Connection,
     lobbies: HashMap<String, lobby::Lobby>,
+    // Running clocks for games with time limits
+    clocks: HashMap<i64, Clock>,
+    // Games where a task is moving for the AI players
+    ai_runners: HashSet<i64>,
     // Room -> Username -> Sender
     websockets: HashMap<String, HashMap<String, WebsocketSender>>,
 }
@@ -127,12 +160,20 @@ impl AppState {
          )",
             [],
         )?;
+        // Older databases were created without the history and result
+        // columns, so add them here. This fails harmlessly if they already
+        // exist.
+        let _ = conn.execute("ALTER TABLE games ADD COLUMN history JSON", []);
+        let _ = conn.execute("ALTER TABLE games ADD COLUMN result JSON", []);
         // Remove any incomplete games.
         conn.execute("DELETE FROM games WHERE end_time IS NULL", [])?;
         Ok(Self {
             games: HashMap::new(),
+            game_settings: HashMap::new(),
             conn,
             lobbies: HashMap::new(),
+            clocks: HashMap::new(),
+            ai_runners: HashSet::new(),
             websockets: HashMap::new(),
         })
     }
@@ -153,32 +194,61 @@ impl AppState {
         lobby_code: &str,
         username: &str,
     ) -> Result<i64> {
-        let mut lobby =
-            self.lobbies.remove(lobby_code).ok_or("No such lobby")?;
-        match lobby.run_pregame_checks(username) {
-            Ok(()) => {}
-            Err(e) => {
-                let ret = e.into();
-                self.lobbies.insert(lobby_code.to_owned(), lobby);
-                return Err(ret);
-            }
+        let lobby = self.lobbies.get(lobby_code).ok_or("No such lobby")?;
+        lobby.run_pregame_checks(username)?;
+        // Set the game up from a copy, so the lobby is still there if it
+        // can't start.
+        let mut lobby = lobby.clone();
+        lobby.prepare_for_game()?;
+        let player_names = serde_json::to_string(&lobby.player_names())?;
+        let mut rng = rand::rng();
+        let mut gm = GameManager::new(&mut rng);
+        let settings = lobby.settings().clone();
+        let rules = lobby.rules();
+        gm.set_min_start_spacing(settings.start_spacing)?;
+        for (user, position) in lobby.into_seated_players() {
+            gm.register_player(user, position)?;
         }
-        lobby.prepare_for_game();
-        let now = Utc::now();
+        gm.set_rules(rules)?;
+        self.lobbies.remove(lobby_code);
         self.conn.execute(
             "INSERT INTO games (start_time, player_ids) VALUES (?1, ?2)",
-            [
-                now.to_rfc3339(),
-                serde_json::to_string(&lobby.player_names())?,
-            ],
+            [Utc::now().to_rfc3339(), player_names],
         )?;
         let game_id = self.conn.last_insert_rowid();
-        let mut rng = rand::rng();
-        let mut gm = GameManager::new(&mut rng);
-        for (user, position) in lobby.into_seated_players() {
-            gm.register_player(user, position)?;
+        let first = &gm.current_player().username;
+        if let Some(clock) = Clock::new(&settings, first, Instant::now()) {
+            self.clocks.insert(game_id, clock);
         }
         self.games.insert(game_id, gm);
+        self.game_settings.insert(game_id, settings);
+        Ok(game_id)
+    }
+
+    // Starts a single-player game from a puzzle position. The solver moves
+    // first, and AI players fill the other seats.
+    pub fn new_puzzle_game(
+        &mut self,
+        puzzle: &Puzzle,
+        username: String,
+    ) -> Result<i64> {
+        let mut names = vec![username];
+        for i in 1..puzzle.players.len() {
+            names.push(format!("{}{}", lobby::AI_NAME_PREFIX, i));
+        }
+        let gm = GameManager::from_puzzle(puzzle, names.clone())?;
+        self.conn.execute(
+            "INSERT INTO games (start_time, player_ids) VALUES (?1, ?2)",
+            [Utc::now().to_rfc3339(), serde_json::to_string(&names[..1])?],
+        )?;
+        let game_id = self.conn.last_insert_rowid();
+        self.games.insert(game_id, gm);
+        // A hint would give away the answer.
+        let settings = lobby::GameSettings {
+            allow_hints: false,
+            ..Default::default()
+        };
+        self.game_settings.insert(game_id, settings);
         Ok(game_id)
     }
 
@@ -276,102 +346,631 @@ impl AppState {
         };
     }
 
+    fn set_lobby_hints_helper(
+        &mut self,
+        lobby_code: &str,
+        allow: bool,
+        username: &str,
+    ) -> Result<&lobby::Lobby> {
+        let lobby = self.lobbies.get_mut(lobby_code).ok_or("No such lobby")?;
+        if lobby.host() != username {
+            return Err(NotHostError.into());
+        }
+        lobby.set_allow_hints(allow);
+        Ok(lobby)
+    }
+
+    pub fn set_lobby_hints(
+        &mut self,
+        lobby_code: &str,
+        allow: bool,
+        username: &str,
+    ) {
+        match self.set_lobby_hints_helper(lobby_code, allow, username) {
+            Ok(lobby) => {
+                let msg =
+                    serde_json::to_string(&LobbyResponse::Update { lobby })
+                        .unwrap();
+                self.broadcast_to_room(msg, lobby_code, None);
+            }
+            Err(e) => {
+                let msg = serde_json::to_string(&LobbyResponse::Error {
+                    message: e.to_string(),
+                })
+                .unwrap();
+                self.send_to_user(msg, lobby_code, username);
+            }
+        };
+    }
+
+    fn set_lobby_team_helper(
+        &mut self,
+        lobby_code: &str,
+        team: usize,
+        username: &str,
+    ) -> Result<&lobby::Lobby> {
+        let lobby = self.lobbies.get_mut(lobby_code).ok_or("No such lobby")?;
+        lobby.set_team(team, username)?;
+        Ok(lobby)
+    }
+
+    pub fn set_lobby_team(
+        &mut self,
+        lobby_code: &str,
+        team: usize,
+        username: &str,
+    ) {
+        match self.set_lobby_team_helper(lobby_code, team, username) {
+            Ok(lobby) => {
+                let msg =
+                    serde_json::to_string(&LobbyResponse::Update { lobby })
+                        .unwrap();
+                self.broadcast_to_room(msg, lobby_code, None);
+            }
+            Err(e) => {
+                let msg = serde_json::to_string(&LobbyResponse::Error {
+                    message: e.to_string(),
+                })
+                .unwrap();
+                self.send_to_user(msg, lobby_code, username);
+            }
+        };
+    }
+
+    fn set_lobby_option_helper(
+        &mut self,
+        lobby_code: &str,
+        option: &str,
+        value: bool,
+        username: &str,
+    ) -> Result<&lobby::Lobby> {
+        let lobby = self.lobbies.get_mut(lobby_code).ok_or("No such lobby")?;
+        if lobby.host() != username {
+            return Err(NotHostError.into());
+        }
+        lobby.set_option(option, value)?;
+        Ok(lobby)
+    }
+
+    pub fn set_lobby_option(
+        &mut self,
+        lobby_code: &str,
+        option: &str,
+        value: bool,
+        username: &str,
+    ) {
+        match self.set_lobby_option_helper(lobby_code, option, value, username)
+        {
+            Ok(lobby) => {
+                let msg =
+                    serde_json::to_string(&LobbyResponse::Update { lobby })
+                        .unwrap();
+                self.broadcast_to_room(msg, lobby_code, None);
+            }
+            Err(e) => {
+                let msg = serde_json::to_string(&LobbyResponse::Error {
+                    message: e.to_string(),
+                })
+                .unwrap();
+                self.send_to_user(msg, lobby_code, username);
+            }
+        };
+    }
+
+    fn set_lobby_clock_helper(
+        &mut self,
+        lobby_code: &str,
+        turn_seconds: u64,
+        game_seconds: u64,
+        username: &str,
+    ) -> Result<&lobby::Lobby> {
+        let lobby = self.lobbies.get_mut(lobby_code).ok_or("No such lobby")?;
+        if lobby.host() != username {
+            return Err(NotHostError.into());
+        }
+        lobby.set_time_limits(turn_seconds, game_seconds)?;
+        Ok(lobby)
+    }
+
+    pub fn set_lobby_clock(
+        &mut self,
+        lobby_code: &str,
+        turn_seconds: u64,
+        game_seconds: u64,
+        username: &str,
+    ) {
+        match self.set_lobby_clock_helper(
+            lobby_code,
+            turn_seconds,
+            game_seconds,
+            username,
+        ) {
+            Ok(lobby) => {
+                let msg =
+                    serde_json::to_string(&LobbyResponse::Update { lobby })
+                        .unwrap();
+                self.broadcast_to_room(msg, lobby_code, None);
+            }
+            Err(e) => {
+                let msg = serde_json::to_string(&LobbyResponse::Error {
+                    message: e.to_string(),
+                })
+                .unwrap();
+                self.send_to_user(msg, lobby_code, username);
+            }
+        };
+    }
+
+    fn set_lobby_spacing_helper(
+        &mut self,
+        lobby_code: &str,
+        spacing: board::EdgePos,
+        username: &str,
+    ) -> Result<&lobby::Lobby> {
+        let lobby = self.lobbies.get_mut(lobby_code).ok_or("No such lobby")?;
+        if lobby.host() != username {
+            return Err(NotHostError.into());
+        }
+        lobby.set_start_spacing(spacing)?;
+        Ok(lobby)
+    }
+
+    pub fn set_lobby_spacing(
+        &mut self,
+        lobby_code: &str,
+        spacing: board::EdgePos,
+        username: &str,
+    ) {
+        match self.set_lobby_spacing_helper(lobby_code, spacing, username) {
+            Ok(lobby) => {
+                let msg =
+                    serde_json::to_string(&LobbyResponse::Update { lobby })
+                        .unwrap();
+                self.broadcast_to_room(msg, lobby_code, None);
+            }
+            Err(e) => {
+                let msg = serde_json::to_string(&LobbyResponse::Error {
+                    message: e.to_string(),
+                })
+                .unwrap();
+                self.send_to_user(msg, lobby_code, username);
+            }
+        };
+    }
+
+    fn set_lobby_scoring_helper(
+        &mut self,
+        lobby_code: &str,
+        scoring: Scoring,
+        username: &str,
+    ) -> Result<&lobby::Lobby> {
+        let lobby = self.lobbies.get_mut(lobby_code).ok_or("No such lobby")?;
+        if lobby.host() != username {
+            return Err(NotHostError.into());
+        }
+        lobby.set_scoring(scoring)?;
+        Ok(lobby)
+    }
+
+    pub fn set_lobby_scoring(
+        &mut self,
+        lobby_code: &str,
+        scoring: Scoring,
+        username: &str,
+    ) {
+        match self.set_lobby_scoring_helper(lobby_code, scoring, username) {
+            Ok(lobby) => {
+                let msg =
+                    serde_json::to_string(&LobbyResponse::Update { lobby })
+                        .unwrap();
+                self.broadcast_to_room(msg, lobby_code, None);
+            }
+            Err(e) => {
+                let msg = serde_json::to_string(&LobbyResponse::Error {
+                    message: e.to_string(),
+                })
+                .unwrap();
+                self.send_to_user(msg, lobby_code, username);
+            }
+        };
+    }
+
     pub fn game(&self, game_id: i64) -> Option<&GameManager> {
         self.games.get(&game_id)
     }
 
+    pub fn hint(&self, game_id: i64, username: &str) -> Result<Suggestion> {
+        let game = self.games.get(&game_id).ok_or("Invalid game ID")?;
+        let allowed = self
+            .game_settings
+            .get(&game_id)
+            .is_none_or(|s| s.allow_hints);
+        if !allowed {
+            return Err("Hints are disabled for this game".into());
+        }
+        if game.is_over() {
+            return Err("Game is over".into());
+        }
+        if game.is_drafting() {
+            return Err("Tiles are still being drafted".into());
+        }
+        Ok(game
+            .suggest_move(username, HINT_STRENGTH)
+            .ok_or("Player not found.")?)
+    }
+
     fn take_turn_helper(
         &mut self,
         params: TurnParams,
         username: &str,
-    ) -> Result<(&board::Board, GameStatus)> {
+    ) -> Result<Option<GameResult>> {
         let game = self
             .games
             .get_mut(&params.game_id)
             .ok_or("Invalid game ID")?;
+        if game.is_over() {
+            return Err("Game is over".into());
+        }
         if game.current_player().username != username {
             return Err(NotYourTurnError.into());
         }
-        let mut winners = game.take_turn(params.idx, params.facing);
-        // HACK: Handle AI player moves.
-        while winners.is_none()
-            && game.current_player().username.starts_with("AI player #")
-        {
-            let ai_move = AvoidSuddenDeathAgent::default().choose_action(game);
-            winners = game.take_turn(ai_move.0, ai_move.1);
+        let result =
+            game.take_turn_with_spin(params.idx, params.facing, params.spin)?;
+        if let Some(result) = &result {
+            self.record_result(params.game_id, result)?;
         }
-        let status = if let Some(winner_names) = winners {
-            // Game is over, record the result in the DB.
-            let now = Utc::now();
-            self.conn.execute(
-                "UPDATE games
-            SET board_state = ?1, end_time = ?2
+        Ok(result)
+    }
+
+    // Game is over, record the result in the DB.
+    fn record_result(&self, game_id: i64, result: &GameResult) -> Result<()> {
+        let game = self.games.get(&game_id).ok_or("Invalid game ID")?;
+        let now = Utc::now();
+        self.conn.execute(
+            "UPDATE games
+            SET board_state = ?1, end_time = ?2, history = ?4, result = ?5
             WHERE id = ?3 LIMIT 1",
-                [
-                    serde_json::to_string(&game.board)?,
-                    now.to_rfc3339(),
-                    params.game_id.to_string(),
-                ],
-            )?;
-            let players_json = self.conn.query_row(
-                "SELECT player_ids FROM games WHERE id = ?1 LIMIT 1",
-                [params.game_id.to_string()],
-                |row| row.get::<usize, String>(0),
-            )?;
-            let player_names: Vec<String> =
-                serde_json::from_str(&players_json)?;
-            for name in player_names.into_iter() {
-                self.conn.execute(
-                    "UPDATE players
+            [
+                serde_json::to_string(&game.board)?,
+                now.to_rfc3339(),
+                game_id.to_string(),
+                serde_json::to_string(game.history())?,
+                serde_json::to_string(result)?,
+            ],
+        )?;
+        let players_json = self.conn.query_row(
+            "SELECT player_ids FROM games WHERE id = ?1 LIMIT 1",
+            [game_id.to_string()],
+            |row| row.get::<usize, String>(0),
+        )?;
+        let player_names: Vec<String> = serde_json::from_str(&players_json)?;
+        for name in player_names.into_iter() {
+            self.conn.execute(
+                "UPDATE players
                 SET num_games = num_games + 1, last_game = ?1
                 WHERE username = ?2 LIMIT 1",
-                    [now.to_rfc3339(), name],
-                )?;
-            }
-            match winner_names.len() {
-                0 => GameStatus::EveryoneLoses,
-                1 => GameStatus::Winner(winner_names[0].clone()),
-                // TODO: Allow having multiple winners.
-                _ => GameStatus::Winner(winner_names[0].clone()),
-            }
-        } else {
-            GameStatus::Ongoing
-        };
-        Ok((&game.board, status))
+                [now.to_rfc3339(), name],
+            )?;
+        }
+        Ok(())
     }
 
     pub fn take_turn(&mut self, params: TurnParams, username: &str) {
-        let room = &params.game_id.to_string();
+        let game_id = params.game_id;
         match self.take_turn_helper(params, username) {
-            Ok((board, status)) => {
-                let resp = match status {
-                    GameStatus::Ongoing => TurnResponse::Update { board },
-                    GameStatus::Winner(winner) => {
-                        TurnResponse::GameOver { winner, board }
+            Ok(result) => self.announce_turn(game_id, result, true),
+            Err(e) => {
+                let msg = serde_json::to_string(&TurnResponse::Error {
+                    message: e.to_string(),
+                })
+                .unwrap();
+                self.send_to_user(msg, &game_id.to_string(), username);
+            }
+        }
+    }
+
+    // Tells everyone in the game what changed, and starts the clock for
+    // whoever is up next if a new turn has begun.
+    fn announce_turn(
+        &mut self,
+        game_id: i64,
+        result: Option<GameResult>,
+        new_turn: bool,
+    ) {
+        let Some(game) = self.games.get(&game_id) else {
+            return;
+        };
+        let resp = match result {
+            None => {
+                let now = Instant::now();
+                let clock = self.clocks.get_mut(&game_id).map(|clock| {
+                    if new_turn {
+                        let next = &game.current_player().username;
+                        clock.start_turn(next, now);
                     }
-                    GameStatus::EveryoneLoses => TurnResponse::GameOver {
-                        winner: "".to_owned(),
-                        board,
-                    },
+                    clock.view(now)
+                });
+                TurnResponse::Update {
+                    board: &game.board,
+                    clock,
+                }
+            }
+            Some(result) => {
+                self.clocks.remove(&game_id);
+                TurnResponse::GameOver {
+                    board: &game.board,
+                    result,
+                }
+            }
+        };
+        let msg = serde_json::to_string(&resp).unwrap();
+        self.broadcast_to_room(msg, &game_id.to_string(), None);
+    }
+
+    pub fn clock_view(&self, game_id: i64) -> Option<ClockView> {
+        Some(self.clocks.get(&game_id)?.view(Instant::now()))
+    }
+
+    fn draft_tile_helper(
+        &mut self,
+        game_id: i64,
+        idx: usize,
+        username: &str,
+    ) -> Result<()> {
+        let game = self.games.get_mut(&game_id).ok_or("Invalid game ID")?;
+        if game.current_player().username != username {
+            return Err(NotYourTurnError.into());
+        }
+        game.draft_tile(idx)?;
+        Ok(())
+    }
+
+    pub fn draft_tile(&mut self, params: DraftParams, username: &str) {
+        let game_id = params.game_id;
+        match self.draft_tile_helper(game_id, params.idx, username) {
+            Ok(()) => self.announce_turn(game_id, None, true),
+            Err(e) => {
+                let msg = serde_json::to_string(&TurnResponse::Error {
+                    message: e.to_string(),
+                })
+                .unwrap();
+                self.send_to_user(msg, &game_id.to_string(), username);
+            }
+        }
+    }
+
+    // Applies a draft pick chosen by an AI player. Returns false if it failed.
+    pub fn take_ai_draft(
+        &mut self,
+        game_id: i64,
+        username: &str,
+        idx: usize,
+    ) -> bool {
+        match self.draft_tile_helper(game_id, idx, username) {
+            Ok(()) => {
+                self.announce_turn(game_id, None, true);
+                true
+            }
+            Err(e) => {
+                error!("AI draft failed for {}: {}", username, e);
+                false
+            }
+        }
+    }
+
+    // If an AI player is up next, returns their name and what they can see.
+    fn pending_ai_turn(
+        &self,
+        game_id: i64,
+    ) -> Option<(String, PlayerObservation)> {
+        let game = self.games.get(&game_id)?;
+        if game.is_over() {
+            return None;
+        }
+        let username = &game.current_player().username;
+        if !is_ai_player(username) {
+            return None;
+        }
+        Some((username.clone(), game.current_observation()))
+    }
+
+    // Claims the AI players' turns in a game, so only one task moves for them
+    // at a time. Returns false if another task already has them.
+    pub fn claim_ai_turns(&mut self, game_id: i64) -> bool {
+        self.ai_runners.insert(game_id)
+    }
+
+    // The next AI turn, for the task that claimed the game. When it's a
+    // human's turn, the claim is given up in the same step, so a task
+    // started by that human's move can't be turned away.
+    pub fn next_ai_turn(
+        &mut self,
+        game_id: i64,
+    ) -> Option<(String, PlayerObservation)> {
+        let turn = self.pending_ai_turn(game_id);
+        if turn.is_none() {
+            self.release_ai_turns(game_id);
+        }
+        turn
+    }
+
+    pub fn release_ai_turns(&mut self, game_id: i64) {
+        self.ai_runners.remove(&game_id);
+    }
+
+    // Applies a move chosen by an AI player, or None if it couldn't choose one.
+    // A failed move falls back to a safe one, so the game doesn't stall.
+    // Returns false if the AI player couldn't move at all.
+    pub fn take_ai_turn(
+        &mut self,
+        game_id: i64,
+        username: &str,
+        choice: Option<(usize, Direction)>,
+    ) -> bool {
+        let result = match choice {
+            Some((idx, facing)) => {
+                let params = TurnParams {
+                    game_id,
+                    idx,
+                    facing,
+                    spin: None,
                 };
-                let msg = serde_json::to_string(&resp).unwrap();
-                self.broadcast_to_room(msg, room, None);
+                self.take_turn_helper(params, username)
+            }
+            None => Err("No move chosen".into()),
+        };
+        let result = result.or_else(|e| {
+            error!("AI move failed for {}: {}", username, e);
+            self.ai_fallback_helper(game_id, username)
+        });
+        match result {
+            Ok(result) => {
+                self.announce_turn(game_id, result, true);
+                true
+            }
+            Err(e) => {
+                error!("AI fallback failed for {}: {}", username, e);
+                false
+            }
+        }
+    }
+
+    // Plays a safe move for an AI player whose own move failed, or takes
+    // them out of the game if even that doesn't work.
+    fn ai_fallback_helper(
+        &mut self,
+        game_id: i64,
+        username: &str,
+    ) -> Result<Option<GameResult>> {
+        let game = self.games.get_mut(&game_id).ok_or("Invalid game ID")?;
+        if game.is_over() {
+            return Err("Game is over".into());
+        }
+        if game.current_player().username != username {
+            return Err(NotYourTurnError.into());
+        }
+        let obs = game.current_observation();
+        let (idx, facing) =
+            AvoidSuddenDeathAgent.choose_action(&obs, Instant::now());
+        let result = match game.take_turn_with_spin(idx, facing, None) {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Safe move failed for {}: {}", username, e);
+                game.forfeit_turn()?
+            }
+        };
+        if let Some(result) = &result {
+            self.record_result(game_id, result)?;
+        }
+        Ok(result)
+    }
+
+    fn resign_helper(
+        &mut self,
+        game_id: i64,
+        username: &str,
+    ) -> Result<(Option<GameResult>, bool)> {
+        let game = self.games.get_mut(&game_id).ok_or("Invalid game ID")?;
+        let was_their_turn =
+            !game.is_over() && game.current_player().username == username;
+        let result = game.resign(username)?;
+        if let Some(result) = &result {
+            self.record_result(game_id, result)?;
+        }
+        Ok((result, was_their_turn))
+    }
+
+    // Takes a player out of the game, whether or not it's their turn.
+    pub fn resign(&mut self, game_id: i64, username: &str) {
+        match self.resign_helper(game_id, username) {
+            Ok((result, was_their_turn)) => {
+                info!("{} resigned from game {}", username, game_id);
+                // Resigning out of turn doesn't restart anyone's clock.
+                self.announce_turn(game_id, result, was_their_turn);
             }
             Err(e) => {
                 let msg = serde_json::to_string(&TurnResponse::Error {
                     message: e.to_string(),
                 })
                 .unwrap();
-                self.send_to_user(msg, room, username);
+                self.send_to_user(msg, &game_id.to_string(), username);
             }
         }
     }
 
+    // Moves for every human player who has run out of time. Returns the
+    // games that moved on, since AI players may be up next.
+    pub fn handle_timeouts(&mut self, now: Instant) -> Vec<i64> {
+        let expired: Vec<(i64, String)> = self
+            .clocks
+            .iter()
+            .filter(|(_, clock)| {
+                clock.deadline() <= now && !is_ai_player(clock.current_player())
+            })
+            .map(|(&id, clock)| (id, clock.current_player().to_owned()))
+            .collect();
+        let mut moved = Vec::new();
+        for (game_id, username) in expired {
+            match self.timeout_helper(game_id, &username) {
+                Ok(result) => {
+                    info!("{} ran out of time in game {}", &username, game_id);
+                    self.announce_turn(game_id, result, true);
+                    moved.push(game_id);
+                }
+                Err(e) => {
+                    error!("Timeout move failed for {}: {}", &username, e);
+                    // Don't keep retrying a game that's stuck.
+                    self.clocks.remove(&game_id);
+                }
+            }
+        }
+        moved
+    }
+
+    // Takes the turn of a player who ran out of time: a safe move if there is
+    // one, or knocking them out if the host chose that instead.
+    fn timeout_helper(
+        &mut self,
+        game_id: i64,
+        username: &str,
+    ) -> Result<Option<GameResult>> {
+        let game = self.games.get_mut(&game_id).ok_or("Invalid game ID")?;
+        if game.is_over() {
+            return Err("Game is over".into());
+        }
+        if game.current_player().username != username {
+            return Err(NotYourTurnError.into());
+        }
+        let obs = game.current_observation();
+        if game.is_drafting() {
+            game.draft_tile(AvoidSuddenDeathAgent.choose_draft_pick(&obs))?;
+            return Ok(None);
+        }
+        let eliminate = self
+            .game_settings
+            .get(&game_id)
+            .is_some_and(|s| s.eliminate_on_timeout);
+        let result = if eliminate {
+            game.forfeit_turn()?
+        } else {
+            let (idx, facing) =
+                AvoidSuddenDeathAgent.choose_action(&obs, Instant::now());
+            game.take_turn(idx, facing)?
+        };
+        if let Some(result) = &result {
+            self.record_result(game_id, result)?;
+        }
+        Ok(result)
+    }
+
     pub fn sign_up(
         &mut self,
         creds: UserCredentials,
         secret: &jsonwebtoken::EncodingKey,
     ) -> Result<String> {
+        if is_ai_player(&creds.username) {
+            return Err(SignupError.into());
+        }
         let salt = rand::rng().random::<[u8; 32]>();
         let config = Config::default();
         let hash = argon2::hash_encoded(&creds.password, &salt, &config)?;
@@ -431,7 +1030,7 @@ impl AppState {
     ) {
         self.websockets
             .entry(room.to_owned())
-            .or_insert_with(HashMap::new)
+            .or_default()
             .insert(username.to_owned(), tx);
     }
 
@@ -450,7 +1049,11 @@ impl AppState {
         room: &str,
         sender: Option<&str>,
     ) {
-        for (user, tx) in self.websockets[room].iter() {
+        // Everyone may have left, e.g. when a clock runs out.
+        let Some(users) = self.websockets.get(room) else {
+            return;
+        };
+        for (user, tx) in users.iter() {
             if sender != Some(user) {
                 // If the recipient disconnected, that's not our problem.
                 let _ = tx.send(warp::ws::Message::text(msg.clone()));
@@ -459,10 +1062,16 @@ impl AppState {
     }
 
     fn send_to_user(&self, msg: String, room: &str, user: &str) {
-        let _ = self.websockets[room][user].send(warp::ws::Message::text(msg));
+        if let Some(tx) = self.websockets.get(room).and_then(|r| r.get(user)) {
+            let _ = tx.send(warp::ws::Message::text(msg));
+        }
     }
 }
 
+fn is_ai_player(username: &str) -> bool {
+    username.starts_with(lobby::AI_NAME_PREFIX)
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct Claims {
     sub: String,
@@ -496,3 +1105,100 @@ pub fn decode_jwt(
     )?;
     Ok(decoded.claims.sub)
 }
+
+#[test]
+fn test_send_to_empty_room() {
+    // Nobody is connected, so these should do nothing rather than panic.
+    let app = AppState::new(":memory:").unwrap();
+    app.broadcast_to_room("hi".to_owned(), "1", None);
+    app.send_to_user("hi".to_owned(), "1", "Bob");
+}
+
+#[test]
+fn test_ai_names_reserved() {
+    let mut app = AppState::new(":memory:").unwrap();
+    let secret = jsonwebtoken::EncodingKey::from_secret(b"secret");
+    let creds = |username: &str| UserCredentials {
+        username: username.to_owned(),
+        password: b"hunter2".to_vec(),
+    };
+    assert!(app.sign_up(creds("AI player #1"), &secret).is_err());
+    assert!(app.sign_up(creds("Bob"), &secret).is_ok());
+}
+
+#[test]
+fn test_ai_turn_fallback() {
+    let mut app = AppState::new(":memory:").unwrap();
+    let mut gm = GameManager::with_tile_stack(strecke::tiles::all_tiles());
+    let ai_name = format!("{}1", lobby::AI_NAME_PREFIX);
+    gm.register_player(ai_name.clone(), board::edge_position(0))
+        .unwrap();
+    gm.register_player("Bob".into(), board::edge_position(24))
+        .unwrap();
+    gm.set_rules(Default::default()).unwrap();
+    app.games.insert(1, gm);
+    assert_eq!(app.pending_ai_turn(1).unwrap().0, ai_name);
+
+    let game = &app.games[&1];
+    let hand = game.get_player(&ai_name).unwrap().tiles_in_hand.clone();
+    let trail = game.board.players[0].clone();
+    let target = trail.last().unwrap().next_tile_position();
+    assert_eq!(game.board.get_tile(&target), Some(&None));
+
+    // A bad choice still moves the game on, with a safe move instead.
+    assert!(app.take_ai_turn(1, &ai_name, Some((99, Direction::North))));
+    let game = &app.games[&1];
+    assert_eq!(game.current_player().username, "Bob");
+    let ai = game.get_player(&ai_name).unwrap();
+    assert_ne!(ai.tiles_in_hand, hand);
+    assert!(game.board.players[0].len() > trail.len());
+    assert!(game.board.get_tile(&target).unwrap().is_some());
+    // It's no longer the AI player's turn.
+    assert!(!app.take_ai_turn(1, &ai_name, None));
+}
+
+#[test]
+fn test_draft_errors() {
+    let mut app = AppState::new(":memory:").unwrap();
+    let mut gm = GameManager::with_tile_stack(strecke::tiles::all_tiles());
+    gm.register_player("Ann".into(), board::edge_position(0))
+        .unwrap();
+    gm.register_player("Bob".into(), board::edge_position(24))
+        .unwrap();
+    gm.set_rules(strecke::rules::Rules {
+        draft: true,
+        ..Default::default()
+    })
+    .unwrap();
+    app.games.insert(1, gm);
+    let err = app.hint(1, "Ann").unwrap_err();
+    assert_eq!(err.to_string(), "Tiles are still being drafted");
+    let params = TurnParams {
+        game_id: 1,
+        idx: 0,
+        facing: Direction::North,
+        spin: None,
+    };
+    let err = app.take_turn_helper(params, "Ann").unwrap_err();
+    assert_eq!(err.to_string(), "Tiles are still being drafted");
+}
+
+#[test]
+fn test_one_ai_runner_per_game() {
+    let mut app = AppState::new(":memory:").unwrap();
+    let mut gm = GameManager::with_tile_stack(strecke::tiles::all_tiles());
+    let ai_name = format!("{}1", lobby::AI_NAME_PREFIX);
+    gm.register_player(ai_name.clone(), board::edge_position(0))
+        .unwrap();
+    gm.register_player("Bob".into(), board::edge_position(24))
+        .unwrap();
+    app.games.insert(1, gm);
+    assert!(app.claim_ai_turns(1));
+    assert!(!app.claim_ai_turns(1));
+    assert!(app.next_ai_turn(1).is_some());
+    assert!(!app.claim_ai_turns(1));
+    assert!(app.take_ai_turn(1, &ai_name, None));
+    // Bob's turn, so the claim goes with it.
+    assert!(app.next_ai_turn(1).is_none());
+    assert!(app.claim_ai_turns(1));
+}
@@ -1,45 +1,234 @@
 use crate::board::{Board, Position};
-use crate::game::GameManager;
+use crate::game::PlayerObservation;
 use crate::tiles::{Direction, Tile};
 use log::info;
+use std::time::Instant;
 
 pub trait Agent {
-    fn choose_action(&self, game: &GameManager) -> (usize, Direction);
+    // Agents should return the best move found so far once the deadline has
+    // passed, rather than finishing their search.
+    fn choose_action(
+        &self,
+        obs: &PlayerObservation,
+        deadline: Instant,
+    ) -> (usize, Direction);
+
+    // Picks a tile from the draft pool before play begins.
+    fn choose_draft_pick(&self, obs: &PlayerObservation) -> usize {
+        safest_draft_pick(obs)
+    }
 }
 
-pub fn create_agent(_difficulty: usize) -> Box<dyn Agent + Send> {
-    Box::<AvoidSuddenDeathAgent>::default()
+// Prefers the pool tile with the most placements that survive from where the
+// player starts.
+fn safest_draft_pick(obs: &PlayerObservation) -> usize {
+    let survivals = |tile: &Tile| {
+        Direction::all()
+            .filter(|&dir| {
+                follow_path(&obs.board, obs.position(), tile, dir).alive
+            })
+            .count()
+    };
+    let mut best = 0;
+    for (i, tile) in obs.draft_pool.iter().enumerate() {
+        if survivals(tile) > survivals(&obs.draft_pool[best]) {
+            best = i;
+        }
+    }
+    best
+}
+
+pub fn create_agent(difficulty: usize) -> Box<dyn Agent + Send> {
+    match difficulty {
+        0 => Box::<AvoidSuddenDeathAgent>::default(),
+        _ => Box::new(LookaheadAgent {
+            max_depth: difficulty + 1,
+        }),
+    }
 }
 
 // Simple tile selection function that only tries to avoid immediate death.
 #[derive(Default)]
 pub struct AvoidSuddenDeathAgent;
 impl Agent for AvoidSuddenDeathAgent {
-    fn choose_action(&self, game: &GameManager) -> (usize, Direction) {
-        let my_pos = game.current_player_pos();
-        let me = game.current_player();
-        assert!(!me.tiles_in_hand.is_empty());
-        for (i, tile) in me.tiles_in_hand.iter().enumerate() {
+    fn choose_action(
+        &self,
+        obs: &PlayerObservation,
+        _deadline: Instant,
+    ) -> (usize, Direction) {
+        let my_pos = obs.position();
+        let me = obs.board_index;
+        assert!(!obs.hand.is_empty());
+        // With other hands in view, it also steers clear of moves an
+        // opponent could punish, if it can.
+        let open = obs.opponents.iter().any(|o| o.hand.is_some());
+        let mut threatened = None;
+        for (i, tile) in obs.hand.iter().enumerate() {
             for dir in Direction::all() {
-                let end_pos = follow_path(&game.board, my_pos, tile, dir);
-                if end_pos.alive {
-                    info!(
-                        "{}: Playing tile {} facing {:?}",
-                        me.username, i, dir
-                    );
-                    return (i, dir);
+                let end_pos = follow_path(&obs.board, my_pos, tile, dir);
+                if !end_pos.alive {
+                    continue;
+                }
+                if open {
+                    let mut board = obs.board.clone();
+                    board.play_tile(me, tile, dir);
+                    if count_threats(obs, &board) > 0 {
+                        threatened.get_or_insert((i, dir));
+                        continue;
+                    }
                 }
+                info!("Player {}: Playing tile {} facing {:?}", me, i, dir);
+                return (i, dir);
             }
         }
+        if let Some((i, dir)) = threatened {
+            info!("Player {}: Playing tile {} facing {:?}", me, i, dir);
+            return (i, dir);
+        }
         // Fallback: no safe tile to play.
         info!(
-            "{}: No safe tile to play, playing arbitrary tile!",
-            me.username
+            "Player {}: No safe tile to play, playing arbitrary tile!",
+            me
         );
         (0, Direction::North)
     }
 }
 
+// Searches through sequences of tiles from its own hand, preferring the move
+// that leaves the most ways to stay alive. Each depth is searched fully before
+// trying the next one, so there is always a complete answer to fall back on
+// when time runs out. When the rules reveal the stack or other hands, it
+// searches its future draws too and avoids moves an opponent could punish.
+pub struct LookaheadAgent {
+    pub max_depth: usize,
+}
+impl Agent for LookaheadAgent {
+    fn choose_action(
+        &self,
+        obs: &PlayerObservation,
+        deadline: Instant,
+    ) -> (usize, Direction) {
+        let mut best = AvoidSuddenDeathAgent.choose_action(obs, deadline);
+        for depth in 2..=self.max_depth.min(obs.hand.len()) {
+            match best_move(obs, depth, deadline) {
+                Some(choice) => best = choice,
+                None => {
+                    info!(
+                        "Player {}: Ran out of time at depth {}",
+                        obs.board_index, depth
+                    );
+                    break;
+                }
+            }
+        }
+        best
+    }
+}
+
+// Returns the move with the most surviving continuations, or None if the
+// deadline passes before the search completes.
+fn best_move(
+    obs: &PlayerObservation,
+    depth: usize,
+    deadline: Instant,
+) -> Option<(usize, Direction)> {
+    // We draw right after each move, then again once everyone else has.
+    let draws: Vec<Tile> = obs
+        .upcoming_tiles
+        .iter()
+        .flatten()
+        .step_by(obs.opponents.len() + 1)
+        .copied()
+        .collect();
+    let mut best = None;
+    let mut best_key = (false, 0);
+    for (i, tile) in obs.hand.iter().enumerate() {
+        let mut rest = obs.hand.clone();
+        rest.remove(i);
+        rest.extend(draws.first());
+        for dir in Direction::all() {
+            let mut board = obs.board.clone();
+            board.play_tile(obs.board_index, tile, dir);
+            let count = count_survivals(
+                &board,
+                obs.board_index,
+                &rest,
+                draws.get(1..).unwrap_or_default(),
+                depth - 1,
+                deadline,
+            )?;
+            let key = (count_threats(obs, &board) == 0, count);
+            if best.is_none() || key > best_key {
+                best = Some((i, dir));
+                best_key = key;
+            }
+        }
+    }
+    best
+}
+
+// Counts the replies from opponents with known hands that would knock this
+// player out straight after the given position.
+fn count_threats(obs: &PlayerObservation, board: &Board) -> usize {
+    let me = obs.board_index;
+    obs.opponents
+        .iter()
+        .filter(|o| board.players[o.board_index].last().unwrap().alive)
+        .filter_map(|o| Some((o.board_index, o.hand.as_ref()?)))
+        .flat_map(|(bidx, hand)| {
+            hand.iter()
+                .flat_map(|tile| Direction::all().map(move |d| (tile, d)))
+                .map(move |(tile, dir)| (bidx, tile, dir))
+        })
+        .filter(|&(bidx, tile, dir)| {
+            let mut next = board.clone();
+            next.play_tile(bidx, tile, dir);
+            !next.players[me].last().unwrap().alive
+        })
+        .count()
+}
+
+// Counts the sequences of `depth` more placements from `hand` that keep the
+// given player alive. Known `draws` refill the hand after each placement.
+fn count_survivals(
+    board: &Board,
+    player_idx: usize,
+    hand: &[Tile],
+    draws: &[Tile],
+    depth: usize,
+    deadline: Instant,
+) -> Option<usize> {
+    if !board.players[player_idx].last().unwrap().alive {
+        return Some(0);
+    }
+    if depth == 0 || hand.is_empty() {
+        return Some(1);
+    }
+    if Instant::now() >= deadline {
+        return None;
+    }
+    let mut total = 0;
+    for (i, tile) in hand.iter().enumerate() {
+        let mut rest = hand.to_vec();
+        rest.remove(i);
+        rest.extend(draws.first());
+        let later = draws.get(1..).unwrap_or_default();
+        for dir in Direction::all() {
+            let mut next = board.clone();
+            next.play_tile(player_idx, tile, dir);
+            total += count_survivals(
+                &next,
+                player_idx,
+                &rest,
+                later,
+                depth - 1,
+                deadline,
+            )?;
+        }
+    }
+    Some(total)
+}
+
 // TODO: refactor this w/ Board::play_tile
 fn follow_path(
     board: &Board,
@@ -51,6 +240,7 @@ fn follow_path(
     let mut pos = start_pos.next_tile_position();
     let tile_row = pos.row;
     let tile_col = pos.col;
+    let entry = pos.port;
     pos.port = played_tile.traverse(pos.port, dir);
     // Follow the path until we fall off the board or hit an empty tile.
     loop {
@@ -64,6 +254,14 @@ fn follow_path(
             // Hit a blank grid cell.
             Some(None) => {
                 if pos.row == tile_row && pos.col == tile_col {
+                    // Back where we started, from an interior seat, and
+                    // we'd go round forever.
+                    if pos.port == entry {
+                        return Position {
+                            alive: false,
+                            ..start_pos.clone()
+                        };
+                    }
                     // Re-traverse our initial tile (from a different port).
                     pos.port = played_tile.traverse(pos.port, dir);
                 } else {
@@ -101,4 +299,89 @@ mod test {
         assert_eq!(end_pos.col, -1);
         assert_eq!(end_pos.port, Port::C);
     }
+
+    #[test]
+    fn test_lookahead_returns_after_deadline() {
+        let mut game = crate::game::GameManager::new(&mut rand::rng());
+        game.register_player("foo".into(), crate::board::edge_position(3))
+            .unwrap();
+        let obs = game.current_observation();
+        let agent = LookaheadAgent { max_depth: 3 };
+        // Even with no time at all, the agent still picks a move.
+        let (tile_idx, _) = agent.choose_action(&obs, Instant::now());
+        assert!(tile_idx < obs.hand.len());
+    }
+
+    #[test]
+    fn test_draft_pick_survives() {
+        let mut game = crate::game::GameManager::with_tile_stack(all_tiles());
+        game.register_player("foo".into(), crate::board::edge_position(0))
+            .unwrap();
+        game.register_player("bar".into(), crate::board::edge_position(20))
+            .unwrap();
+        game.set_rules(crate::rules::Rules {
+            draft: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let obs = game.current_observation();
+        let pick = AvoidSuddenDeathAgent.choose_draft_pick(&obs);
+        let tile = &obs.draft_pool[pick];
+        assert!(Direction::all().any(|dir| {
+            follow_path(&obs.board, obs.position(), tile, dir).alive
+        }));
+    }
+
+    #[test]
+    fn test_threats_need_open_hands() {
+        let mut game = crate::game::GameManager::with_tile_stack(all_tiles());
+        // Both players face the top left corner, so either one's tile there
+        // moves the other too.
+        game.register_player("foo".into(), crate::board::edge_position(0))
+            .unwrap();
+        game.register_player("bar".into(), crate::board::edge_position(46))
+            .unwrap();
+        let obs = game.current_observation();
+        assert_eq!(count_threats(&obs, &obs.board), 0);
+
+        game.set_rules(crate::rules::Rules {
+            open_hands: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let obs = game.current_observation();
+        assert!(count_threats(&obs, &obs.board) > 0);
+    }
+
+    #[test]
+    fn test_simple_agent_dodges_threats() {
+        // Neighbours on the top edge, where the first move that survives
+        // leaves an opening for the other player's open hand.
+        let mut game = crate::game::GameManager::with_tile_stack(all_tiles());
+        game.register_player("foo".into(), crate::board::edge_position(2))
+            .unwrap();
+        game.register_player("bar".into(), crate::board::edge_position(4))
+            .unwrap();
+        game.set_rules(crate::rules::Rules {
+            open_hands: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let obs = game.current_observation();
+        let unpunished = |(i, dir): (usize, Direction)| {
+            let mut board = obs.board.clone();
+            board.play_tile(obs.board_index, &obs.hand[i], dir);
+            board.players[obs.board_index].last().unwrap().alive
+                && count_threats(&obs, &board) == 0
+        };
+        let first_safe = (0..obs.hand.len())
+            .flat_map(|i| Direction::all().map(move |d| (i, d)))
+            .find(|&(i, d)| {
+                follow_path(&obs.board, obs.position(), &obs.hand[i], d).alive
+            })
+            .unwrap();
+        assert!(!unpunished(first_safe));
+        let choice = AvoidSuddenDeathAgent.choose_action(&obs, Instant::now());
+        assert!(unpunished(choice));
+    }
 }
@@ -0,0 +1,306 @@
+use crate::agent::{Agent, create_agent};
+use crate::game::{GameHistory, GameManager};
+use crate::tiles::Direction;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::Serialize;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// A move is a blunder if some alternative had at least this much better
+// chance of surviving.
+const BLUNDER_MARGIN: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Blunder {
+    // The move eliminated the player when some other move survived the turn.
+    Eliminated,
+    // The move survived, but left much worse chances than the best move.
+    Blunder,
+}
+
+// How one move compares to the best alternative the player had.
+#[derive(Debug, Clone, Serialize)]
+pub struct MoveReview {
+    // Index of this move in the game history.
+    pub turn: usize,
+    pub tile_idx: usize,
+    pub facing: Direction,
+    // Fraction of rollouts in which the player survived to the end.
+    pub survival: f64,
+    pub best_tile_idx: usize,
+    pub best_facing: Direction,
+    pub best_survival: f64,
+    pub blunder: Option<Blunder>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerReview {
+    pub username: String,
+    pub board_index: usize,
+    pub blunders: usize,
+    pub moves: Vec<MoveReview>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GameAnalysis {
+    pub players: Vec<PlayerReview>,
+}
+
+// Replays a finished game and scores every placement each player could have
+// made, by playing the rest of the game out with agents of a given strength.
+pub struct Analyzer {
+    // Difficulty of the agents used for rollouts.
+    pub strength: usize,
+    // Number of rollouts per candidate move.
+    pub rollouts: usize,
+    // How long rollout agents may think about each move.
+    pub time_budget: Duration,
+    // Seeds the shuffles of undrawn tiles in each rollout.
+    pub seed: u64,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self {
+            strength: 1,
+            rollouts: 8,
+            time_budget: Duration::from_millis(2),
+            seed: 0,
+        }
+    }
+}
+
+impl Analyzer {
+    pub fn analyze(
+        &self,
+        history: &GameHistory,
+    ) -> Result<GameAnalysis, String> {
+        let agent = create_agent(self.strength);
+        // Replaying from the start also applies any forfeits before the
+        // first move.
+        let mut game = GameManager::replay(history, 0)?;
+        let mut players: Vec<PlayerReview> = game
+            .standings()
+            .into_iter()
+            .map(|s| PlayerReview {
+                username: s.username,
+                board_index: s.board_index,
+                blunders: 0,
+                moves: Vec::new(),
+            })
+            .collect();
+        for (turn, m) in history.moves.iter().enumerate() {
+            if game.is_over() {
+                return Err("Too many moves in history".to_owned());
+            }
+            if game.current_player().board_index != m.board_index {
+                return Err(format!(
+                    "Move by player {} out of turn",
+                    m.board_index
+                ));
+            }
+            if let Some(review) = self.review_move(&*agent, &game, turn, m)
+                && let Some(player) =
+                    players.iter_mut().find(|p| p.board_index == m.board_index)
+            {
+                player.blunders += review.blunder.is_some() as usize;
+                player.moves.push(review);
+            }
+            game.take_turn_with_spin(m.tile_idx, m.facing, m.spin)?;
+            game.replay_forfeits(&history.forfeits, turn + 1)?;
+        }
+        Ok(GameAnalysis { players })
+    }
+
+    fn review_move(
+        &self,
+        agent: &dyn Agent,
+        game: &GameManager,
+        turn: usize,
+        m: &crate::game::Move,
+    ) -> Option<MoveReview> {
+        let hand_size = game.current_player().tiles_in_hand.len();
+        if m.tile_idx >= hand_size {
+            return None;
+        }
+        // Every candidate gets the same rollout seed, so they're compared
+        // against the same draws.
+        let seed = self.seed.wrapping_add(turn as u64);
+        let mut chosen = None;
+        let mut best: Option<(usize, Direction, f64)> = None;
+        let mut any_survived = false;
+        for tile_idx in 0..hand_size {
+            for facing in Direction::all() {
+                let (survived, chance) =
+                    self.evaluate(agent, game, tile_idx, facing, seed);
+                any_survived |= survived;
+                if tile_idx == m.tile_idx && facing == m.facing {
+                    chosen = Some((survived, chance));
+                }
+                if best.is_none_or(|(_, _, c)| chance > c) {
+                    best = Some((tile_idx, facing, chance));
+                }
+            }
+        }
+        let (survived, survival) = chosen?;
+        let (best_tile_idx, best_facing, best_survival) = best?;
+        let blunder = if !survived && any_survived {
+            Some(Blunder::Eliminated)
+        } else if best_survival - survival >= BLUNDER_MARGIN {
+            Some(Blunder::Blunder)
+        } else {
+            None
+        };
+        Some(MoveReview {
+            turn,
+            tile_idx: m.tile_idx,
+            facing: m.facing,
+            survival,
+            best_tile_idx,
+            best_facing,
+            best_survival,
+            blunder,
+        })
+    }
+
+    // Returns whether the current player survives the move itself, and the
+    // fraction of rollouts in which they're still alive at the end.
+    fn evaluate(
+        &self,
+        agent: &dyn Agent,
+        game: &GameManager,
+        tile_idx: usize,
+        facing: Direction,
+        seed: u64,
+    ) -> (bool, f64) {
+        let bidx = game.current_player().board_index;
+        let mut after = game.clone();
+        after.take_turn(tile_idx, facing).unwrap();
+        if !after.board.players[bidx].last().unwrap().alive {
+            return (false, 0.0);
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut survived = 0;
+        for _ in 0..self.rollouts.max(1) {
+            let mut rollout = after.clone();
+            rollout.shuffle_stack(&mut rng);
+            while !rollout.is_over() {
+                let obs = rollout.current_observation();
+                let deadline = Instant::now() + self.time_budget;
+                let (idx, facing) = agent.choose_action(&obs, deadline);
+                rollout.take_turn(idx, facing).unwrap();
+            }
+            survived +=
+                rollout.board.players[bidx].last().unwrap().alive as usize;
+        }
+        (true, survived as f64 / self.rollouts.max(1) as f64)
+    }
+}
+
+impl fmt::Display for GameAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for player in self.players.iter() {
+            writeln!(
+                f,
+                "{}: {} blunder(s) in {} move(s)",
+                player.username,
+                player.blunders,
+                player.moves.len()
+            )?;
+            for m in player.moves.iter() {
+                let Some(kind) = m.blunder else { continue };
+                let what = match kind {
+                    Blunder::Eliminated => "eliminated",
+                    Blunder::Blunder => "blunder",
+                };
+                writeln!(
+                    f,
+                    "  turn {}: tile {} facing {:?} ({}, {:.0}% survival); \
+                     tile {} facing {:?} had {:.0}%",
+                    m.turn + 1,
+                    m.tile_idx,
+                    m.facing,
+                    what,
+                    100.0 * m.survival,
+                    m.best_tile_idx,
+                    m.best_facing,
+                    100.0 * m.best_survival
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_analysis_flags_suicide() {
+    use crate::board::edge_position;
+    // Find a deal where foo's first hand has both a move that walks straight
+    // off the board and one that doesn't.
+    let (mut game, idx, facing) = (0..100)
+        .find_map(|seed| {
+            let mut game = GameManager::new(&mut StdRng::seed_from_u64(seed));
+            game.register_player("foo".into(), edge_position(3))
+                .unwrap();
+            game.register_player("bar".into(), edge_position(27))
+                .unwrap();
+            let obs = game.current_observation();
+            let outcomes: Vec<(usize, Direction, bool)> = (0..obs.hand.len())
+                .flat_map(|i| Direction::all().map(move |d| (i, d)))
+                .map(|(i, d)| {
+                    let mut board = obs.board.clone();
+                    board.play_tile(0, &obs.hand[i], d);
+                    (i, d, board.players[0].last().unwrap().alive)
+                })
+                .collect();
+            let &(idx, facing, _) = outcomes.iter().find(|o| !o.2)?;
+            outcomes.iter().any(|o| o.2).then_some((game, idx, facing))
+        })
+        .unwrap();
+    game.take_turn(idx, facing).unwrap();
+    let analyzer = Analyzer {
+        strength: 0,
+        rollouts: 2,
+        ..Analyzer::default()
+    };
+    let analysis = analyzer.analyze(game.history()).unwrap();
+    let review = &analysis.players[0].moves[0];
+    assert_eq!(review.blunder, Some(Blunder::Eliminated));
+    assert_eq!(review.survival, 0.0);
+    assert_eq!(analysis.players[0].blunders, 1);
+    assert!(analysis.to_string().contains("eliminated"));
+}
+
+#[test]
+fn test_analysis_with_forfeit() {
+    use crate::board::edge_position;
+    let mut game = GameManager::new(&mut StdRng::seed_from_u64(1));
+    for (name, seat) in [("foo", 3), ("bar", 19), ("baz", 35)] {
+        game.register_player(name.into(), edge_position(seat))
+            .unwrap();
+    }
+    let agent = create_agent(0);
+    let obs = game.current_observation();
+    let (idx, facing) = agent.choose_action(&obs, Instant::now());
+    game.take_turn(idx, facing).unwrap();
+    // bar runs out of time, and play passes to baz.
+    game.forfeit_turn().unwrap();
+    while !game.is_over() {
+        let obs = game.current_observation();
+        let (idx, facing) = agent.choose_action(&obs, Instant::now());
+        game.take_turn(idx, facing).unwrap();
+    }
+    let analyzer = Analyzer {
+        strength: 0,
+        rollouts: 1,
+        ..Analyzer::default()
+    };
+    let analysis = analyzer.analyze(game.history()).unwrap();
+    let bar = &analysis.players[1];
+    assert_eq!((bar.username.as_str(), bar.board_index), ("bar", 1));
+    assert!(bar.moves.is_empty());
+    let num_reviewed: usize =
+        analysis.players.iter().map(|p| p.moves.len()).sum();
+    assert_eq!(num_reviewed, game.history().moves.len());
+}
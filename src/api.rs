@@ -1,21 +1,83 @@
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use blau_api::{DynSafeGameAPI, GameAPI, PlayerInfo, Result};
-use rand::seq::IteratorRandom;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     agent::{Agent, create_agent},
     board,
-    game::GameManager,
+    game::{GameManager, GameResult, ScoreWeights, Suggestion},
+    puzzle::Puzzle,
+    rules::Rules,
     tiles::{Direction, Tile},
 };
 
+/// How long AI players may think about each move.
+const AGENT_TIME_BUDGET: Duration = Duration::from_millis(500);
+
 /// An action taken by a player.
 #[derive(Debug, Serialize, Deserialize)]
 struct Action {
     tile_idx: usize,
     facing: Direction,
+    /// Which neighbour to turn, when playing a spinner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    spin: Option<Direction>,
+}
+
+/// Anything a player can send to `process_action`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Request {
+    /// Take a turn.
+    Play(Action),
+    /// Pick the tile at this index from the draft pool.
+    Draft { draft: usize },
+    /// Ask for a suggested move from an agent of the given strength.
+    Hint { hint: usize },
+    /// Concede the game. Actions always come from the current player, so
+    /// that's who resigns.
+    Resign { resign: bool },
+}
+
+/// Options for a new game, passed as JSON to `init`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GameParams {
+    disable_hints: bool,
+    /// Start from this position instead of an empty board. Players are
+    /// matched to the puzzle's players in order, so the first one solves it.
+    puzzle: Option<Puzzle>,
+    /// How final standings turn into `player_scores`.
+    score_weights: ScoreWeights,
+    /// Teams are listed in the same order as the players.
+    rules: Rules,
+}
+
+/// What's saved of a finished game: enough to rank it again. The board's
+/// fields sit at the top level, so saves of just the board still load.
+#[derive(Debug, Deserialize, Serialize)]
+struct FinalState {
+    #[serde(flatten)]
+    board: board::Board,
+    #[serde(default)]
+    rules: Rules,
+    #[serde(default)]
+    score_weights: ScoreWeights,
+    /// The move that knocked out each player, by board index.
+    #[serde(default)]
+    eliminated_on: Vec<Option<usize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    puzzle: Option<Puzzle>,
+}
+
+/// Message sent to a player who asked for a hint.
+#[derive(Debug, Serialize)]
+struct HintMessage<'a> {
+    action: &'static str,
+    #[serde(flatten)]
+    suggestion: &'a Suggestion,
 }
 
 /// Generic information about a turn that was taken.
@@ -24,6 +86,32 @@ struct TurnInfo {
     tile: Tile,
     pos: board::Position,
     facing: Direction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spin: Option<Direction>,
+}
+
+/// Message sent to players after someone drafts a tile.
+#[derive(Debug, Serialize)]
+struct DraftMessage<'a> {
+    action: &'static str,
+    #[serde(flatten)]
+    view: PlayerView<'a>,
+    player: &'a str,
+    tile: Tile,
+}
+
+/// Message sent to players after someone resigns.
+#[derive(Debug, Serialize)]
+struct ResignMessage<'a> {
+    action: &'static str,
+    #[serde(flatten)]
+    view: PlayerView<'a>,
+    player: &'a str,
+    is_over: bool,
+    is_winner: bool,
+    /// How the game ended, once it's over.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<&'a GameResult>,
 }
 
 /// Message sent to players after a turn is taken.
@@ -34,6 +122,16 @@ struct TakeTurnMessage<'a> {
     turn: &'a TurnInfo,
     is_over: bool,
     is_winner: bool,
+    /// How the game ended, once it's over.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<&'a GameResult>,
+}
+
+/// Another player's hand, shown when the rules make it public.
+#[derive(Debug, Serialize)]
+struct OtherHand<'a> {
+    player: &'a str,
+    hand: &'a [Tile],
 }
 
 /// View of the game state for a specific player.
@@ -42,6 +140,17 @@ struct PlayerView<'a> {
     board: &'a board::Board,
     hand: Option<&'a [Tile]>,
     curr_player_idx: usize,
+    /// Team of each player, by board index, in team games.
+    #[serde(skip_serializing_if = "<[usize]>::is_empty")]
+    teams: &'a [usize],
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    other_hands: Vec<OtherHand<'a>>,
+    /// Tiles left in the stack, next draw first, when the stack is public.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upcoming_tiles: Option<Vec<Tile>>,
+    /// Face-up tiles still to be drafted, before play begins.
+    #[serde(skip_serializing_if = "<[Tile]>::is_empty")]
+    draft_pool: &'a [Tile],
 }
 
 pub struct StreckeAPI {
@@ -53,6 +162,10 @@ pub struct StreckeAPI {
     agents: HashMap<String, Box<dyn Agent + Send>>,
     // Indicates if the game is over
     game_over: bool,
+    // Whether players may ask for suggested moves
+    allow_hints: bool,
+    // How final standings are scored
+    score_weights: ScoreWeights,
 }
 
 impl StreckeAPI {
@@ -71,6 +184,10 @@ impl StreckeAPI {
                 board: &self.state.board,
                 hand: None,
                 curr_player_idx: 0,
+                teams: &self.state.rules().teams,
+                other_hands: Vec::new(),
+                upcoming_tiles: None,
+                draft_pool: &[],
             });
         }
         let curr_player_id = &self.state.current_player().username;
@@ -79,10 +196,23 @@ impl StreckeAPI {
             .iter()
             .position(|p| p.id == *curr_player_id)
             .ok_or("Invalid player ID")?;
+        let other_hands = self
+            .state
+            .visible_hands(player_id)
+            .into_iter()
+            .map(|p| OtherHand {
+                player: &p.username,
+                hand: &p.tiles_in_hand,
+            })
+            .collect();
         Ok(PlayerView {
             board: &self.state.board,
             hand: self.player_hand(player_id),
             curr_player_idx,
+            teams: &self.state.rules().teams,
+            other_hands,
+            upcoming_tiles: self.state.upcoming_tiles(),
+            draft_pool: self.state.draft_pool(),
         })
     }
     fn do_action<F: FnMut(&str, &str)>(
@@ -90,30 +220,99 @@ impl StreckeAPI {
         action: &Action,
         mut notice_cb: F,
     ) -> Result<()> {
+        if self.state.is_drafting() {
+            return Err("Tiles are still being drafted".into());
+        }
         let turn_info = TurnInfo {
             tile: self.state.current_player().tiles_in_hand[action.tile_idx],
             pos: self.state.current_player_pos().next_tile_position(),
             facing: action.facing,
+            spin: action.spin,
         };
         self.game_over = self
             .state
-            .take_turn(action.tile_idx, action.facing)
+            .take_turn_with_spin(action.tile_idx, action.facing, action.spin)?
             .is_some();
         // Notify all human players of the action.
         for player_id in self.human_player_ids() {
-            let view = self.view(player_id)?;
-            let is_winner = self.game_over && view.hand.is_some();
+            let result = self.state.result();
             let msg = TakeTurnMessage {
-                view,
+                view: self.view(player_id)?,
                 turn: &turn_info,
                 is_over: self.game_over,
-                is_winner,
+                is_winner: result.is_some_and(|r| r.is_winner(player_id)),
+                result,
             };
             let msg = serde_json::to_string(&msg)?;
             notice_cb(player_id, &msg);
         }
         Ok(())
     }
+    fn do_draft<F: FnMut(&str, &str)>(
+        &mut self,
+        pool_idx: usize,
+        mut notice_cb: F,
+    ) -> Result<()> {
+        let player = self.current_player_id().to_owned();
+        let tile = *self
+            .state
+            .draft_pool()
+            .get(pool_idx)
+            .ok_or("Invalid draft pick")?;
+        self.state.draft_tile(pool_idx)?;
+        for player_id in self.human_player_ids() {
+            let msg = DraftMessage {
+                action: "draft",
+                view: self.view(player_id)?,
+                player: &player,
+                tile,
+            };
+            let msg = serde_json::to_string(&msg)?;
+            notice_cb(player_id, &msg);
+        }
+        Ok(())
+    }
+    fn do_resign<F: FnMut(&str, &str)>(
+        &mut self,
+        mut notice_cb: F,
+    ) -> Result<()> {
+        let player = self.current_player_id().to_owned();
+        self.game_over = self.state.resign(&player)?.is_some();
+        for player_id in self.human_player_ids() {
+            let result = self.state.result();
+            let msg = ResignMessage {
+                action: "resign",
+                view: self.view(player_id)?,
+                player: &player,
+                is_over: self.game_over,
+                is_winner: result.is_some_and(|r| r.is_winner(player_id)),
+                result,
+            };
+            let msg = serde_json::to_string(&msg)?;
+            notice_cb(player_id, &msg);
+        }
+        Ok(())
+    }
+    fn send_hint<F: FnMut(&str, &str)>(
+        &self,
+        strength: usize,
+        mut notice_cb: F,
+    ) -> Result<()> {
+        if !self.allow_hints {
+            return Err("Hints are disabled for this game".into());
+        }
+        let player_id = self.current_player_id();
+        let suggestion = self
+            .state
+            .suggest_move(player_id, strength)
+            .ok_or("No move to suggest")?;
+        let msg = serde_json::to_string(&HintMessage {
+            action: "hint",
+            suggestion: &suggestion,
+        })?;
+        notice_cb(player_id, &msg);
+        Ok(())
+    }
     fn human_player_ids(&self) -> impl Iterator<Item = &String> {
         self.player_info
             .iter()
@@ -127,41 +326,92 @@ impl StreckeAPI {
         while !self.game_over
             && let Some(ai) = self.agents.get(self.current_player_id())
         {
-            let (tile_idx, facing) = ai.choose_action(&self.state);
-            self.do_action(&Action { tile_idx, facing }, &mut notice_cb)?;
+            if self.state.is_drafting() {
+                let pick =
+                    ai.choose_draft_pick(&self.state.current_observation());
+                self.do_draft(pick, &mut notice_cb)?;
+                continue;
+            }
+            let (tile_idx, facing) = ai.choose_action(
+                &self.state.current_observation(),
+                Instant::now() + AGENT_TIME_BUDGET,
+            );
+            self.do_action(
+                &Action {
+                    tile_idx,
+                    facing,
+                    spin: None,
+                },
+                &mut notice_cb,
+            )?;
         }
         Ok(())
     }
 }
-impl GameAPI for StreckeAPI {
-    fn init(players: &[PlayerInfo], _params: Option<&str>) -> Result<Self> {
-        let mut rng = rand::rng();
-        let mut state = GameManager::new(&mut rng);
-        let positions =
-            (0..board::NOT_READY).choose_multiple(&mut rng, players.len());
-        let mut agents = HashMap::new();
-        for (player, edge_pos) in players.iter().zip(positions) {
-            state.register_player(
-                player.id.clone(),
-                board::edge_position(edge_pos),
-            )?;
-            if player.level.is_some() {
-                agents.insert(player.id.clone(), create_agent(0));
+impl StreckeAPI {
+    fn with_params(players: &[PlayerInfo], params: GameParams) -> Result<Self> {
+        params.score_weights.validate()?;
+        let mut state = match &params.puzzle {
+            Some(puzzle) => GameManager::from_puzzle(
+                puzzle,
+                players.iter().map(|p| p.id.clone()).collect(),
+            )?,
+            None => {
+                let mut rng = rand::rng();
+                let mut state = GameManager::new(&mut rng);
+                let positions = board::spread_seats(
+                    &[],
+                    players.len(),
+                    board::MIN_START_SPACING,
+                    &mut rng,
+                )
+                .ok_or("Too many players to seat")?;
+                for (player, edge_pos) in players.iter().zip(positions) {
+                    state.register_player(
+                        player.id.clone(),
+                        board::edge_position(edge_pos),
+                    )?;
+                }
+                state
             }
-        }
+        };
+        state.set_rules(params.rules.clone())?;
+        let agents = players
+            .iter()
+            .filter_map(|p| Some((p.id.clone(), create_agent(p.level?))))
+            .collect();
         Ok(Self {
             state,
             player_info: players.to_vec(),
             agents,
             game_over: false,
+            // A hint would give away the answer to a puzzle.
+            allow_hints: !params.disable_hints && params.puzzle.is_none(),
+            score_weights: params.score_weights,
         })
     }
+}
+
+impl GameAPI for StreckeAPI {
+    fn init(players: &[PlayerInfo], params: Option<&str>) -> Result<Self> {
+        let params: GameParams = match params {
+            Some(json) => serde_json::from_str(json)?,
+            None => GameParams::default(),
+        };
+        Self::with_params(players, params)
+    }
 
     fn restore(player_info: &[PlayerInfo], final_state: &str) -> Result<Self> {
-        let board: board::Board = serde_json::from_str(final_state)?;
-        let mut res = Self::init(player_info, None)?;
+        let saved: FinalState = serde_json::from_str(final_state)?;
+        let params = GameParams {
+            disable_hints: true,
+            puzzle: saved.puzzle,
+            score_weights: saved.score_weights,
+            rules: saved.rules,
+        };
+        let mut res = Self::with_params(player_info, params)?;
         res.game_over = true;
-        res.state.board = board;
+        res.state.restore_finished(saved.board, saved.eliminated_on);
         Ok(res)
     }
 
@@ -187,8 +437,17 @@ impl GameAPI for StreckeAPI {
         if self.game_over {
             return Err("Game is over".into());
         }
-        let action: Action = serde_json::from_str(action)?;
-        self.do_action(&action, &mut notice_cb)?;
+        match serde_json::from_str(action)? {
+            Request::Play(action) => self.do_action(&action, &mut notice_cb)?,
+            Request::Draft { draft } => self.do_draft(draft, &mut notice_cb)?,
+            Request::Hint { hint } => return self.send_hint(hint, notice_cb),
+            Request::Resign { resign: true } => {
+                self.do_resign(&mut notice_cb)?
+            }
+            Request::Resign { resign: false } => {
+                return Err("Nothing to do".into());
+            }
+        }
         // Advance to wait for the next player action.
         self.process_agents(&mut notice_cb)?;
         Ok(())
@@ -204,7 +463,13 @@ impl DynSafeGameAPI for StreckeAPI {
         if !self.game_over {
             return Err("Game is not finished".into());
         }
-        Ok(serde_json::to_string(&self.state.board)?)
+        Ok(serde_json::to_string(&FinalState {
+            board: self.state.board.clone(),
+            rules: self.state.history().rules.clone(),
+            score_weights: self.score_weights,
+            eliminated_on: self.state.eliminations().to_vec(),
+            puzzle: self.state.puzzle().cloned(),
+        })?)
     }
 
     fn player_view(&self, player_id: &str) -> Result<String> {
@@ -217,7 +482,8 @@ impl DynSafeGameAPI for StreckeAPI {
     }
 
     fn player_scores(&self) -> Vec<i32> {
-        self.state.player_scores()
+        // Players registered in the same order as `player_info`.
+        self.state.scores(&self.score_weights)
     }
 }
 
@@ -237,6 +503,10 @@ fn exercise_api() {
     let view_json = game.player_view("foo").unwrap();
     assert!(view_json.starts_with("{"));
 
+    // Only spinners can turn other tiles.
+    let spin = r#"{"tile_idx": 1, "facing": "West", "spin": "North"}"#;
+    assert!(game.process_action(spin, |_, _| panic!()).is_err());
+
     let mut num_notices = 0;
     game.process_action(r#"{"tile_idx": 1, "facing": "West"}"#, |id, msg| {
         assert_eq!(id, "foo");
@@ -251,3 +521,245 @@ fn exercise_api() {
         assert_eq!(num_notices, 2);
     }
 }
+
+#[test]
+fn hint_action() {
+    let players = vec![
+        PlayerInfo::human("foo".into()),
+        PlayerInfo::human("bar".into()),
+    ];
+    let mut game: StreckeAPI = GameAPI::init(&players, None).unwrap();
+    let mut hints = Vec::new();
+    game.process_action(r#"{"hint": 1}"#, |id, msg| {
+        hints.push((id.to_owned(), msg.to_owned()));
+    })
+    .unwrap();
+    assert_eq!(hints.len(), 1);
+    assert_eq!(hints[0].0, "foo");
+    assert!(hints[0].1.contains(r#""action":"hint""#));
+    // Asking for a hint doesn't use up the turn.
+    assert_eq!(game.current_player_id(), "foo");
+
+    let params = Some(r#"{"disable_hints": true}"#);
+    let mut game: StreckeAPI = GameAPI::init(&players, params).unwrap();
+    assert!(game.process_action(r#"{"hint": 1}"#, |_, _| {}).is_err());
+}
+
+#[test]
+fn puzzle_params() {
+    use crate::puzzle::{Objective, PuzzlePlayer};
+    let mut game = GameManager::new(&mut rand::rng());
+    game.register_player("a".into(), board::edge_position(5))
+        .unwrap();
+    game.register_player("b".into(), board::edge_position(30))
+        .unwrap();
+    let obs = game.current_observation();
+    let puzzle = Puzzle {
+        board: obs.board.clone(),
+        players: game
+            .alive_players
+            .iter()
+            .enumerate()
+            .map(|(board_index, p)| PuzzlePlayer {
+                board_index,
+                hand: p.tiles_in_hand.clone(),
+            })
+            .collect(),
+        tile_stack: Vec::new(),
+        dragon_holder: None,
+        objective: Objective::ReachCell { row: 5, col: 5 },
+        solution: (0, Direction::North),
+    };
+    let players = vec![
+        PlayerInfo::human("foo".into()),
+        PlayerInfo::ai("bot".into(), 1),
+    ];
+    let params = serde_json::json!({ "puzzle": puzzle }).to_string();
+    let mut game: StreckeAPI = GameAPI::init(&players, Some(&params)).unwrap();
+    assert_eq!(game.current_player_id(), "foo");
+    assert!(game.process_action(r#"{"hint": 1}"#, |_, _| {}).is_err());
+    // The puzzle is over after one move, without the bot taking a turn.
+    let mut notices = Vec::new();
+    game.process_action(r#"{"tile_idx": 0, "facing": "North"}"#, |id, msg| {
+        assert_eq!(id, "foo");
+        notices.push(serde_json::from_str::<serde_json::Value>(msg).unwrap());
+    })
+    .unwrap();
+    assert_eq!(notices.len(), 1);
+    assert!(game.is_game_over());
+    // The notice says how it ended, and whether this player won.
+    let solved = game.state.puzzle_solved().unwrap();
+    let outcome = if solved { "Winner" } else { "NoWinner" };
+    assert_eq!(notices[0]["result"]["outcome"], outcome);
+    assert_eq!(notices[0]["is_winner"], solved);
+    // A puzzle needs the right number of players.
+    let params = Some(params.as_str());
+    assert!(StreckeAPI::init(&players[..1], params).is_err());
+}
+
+#[test]
+fn score_weights_params() {
+    let players = vec![
+        PlayerInfo::human("foo".into()),
+        PlayerInfo::human("bar".into()),
+    ];
+    let params =
+        r#"{"score_weights": {"per_player_outlasted": 1, "per_path": 0}}"#;
+    let mut game: StreckeAPI = GameAPI::init(&players, Some(params)).unwrap();
+    while !game.is_game_over() {
+        let action = r#"{"tile_idx": 0, "facing": "North"}"#;
+        game.process_action(action, |_, _| {}).unwrap();
+    }
+    // Scores only count who outlasted whom, in player order.
+    let standings = game.state.standings();
+    let expected: Vec<i32> = standings
+        .iter()
+        .map(|s| {
+            standings
+                .iter()
+                .filter(|o| o.placement > s.placement)
+                .count()
+        })
+        .map(|outlasted| outlasted as i32)
+        .collect();
+    assert_eq!(standings[0].username, "foo");
+    assert_eq!(game.player_scores(), expected);
+
+    let params = r#"{"score_weights": {"per_player_outlasted": 1}}"#;
+    assert!(StreckeAPI::init(&players, Some(params)).is_err());
+}
+
+#[test]
+fn team_params() {
+    let players: Vec<PlayerInfo> = ["a", "b", "c", "d"]
+        .into_iter()
+        .map(|id| PlayerInfo::human(id.into()))
+        .collect();
+    let params =
+        r#"{"rules": {"teams": [0, 1, 0, 1], "open_team_hands": true}}"#;
+    let game: StreckeAPI = GameAPI::init(&players, Some(params)).unwrap();
+    let view: serde_json::Value =
+        serde_json::from_str(&game.player_view("b").unwrap()).unwrap();
+    assert_eq!(view["teams"], serde_json::json!([0, 1, 0, 1]));
+    assert_eq!(view["other_hands"][0]["player"], "d");
+    assert_eq!(view["other_hands"][0]["hand"].as_array().unwrap().len(), 3);
+    assert!(view.get("upcoming_tiles").is_none());
+
+    let params = r#"{"rules": {"teams": [0, 1]}}"#;
+    assert!(StreckeAPI::init(&players, Some(params)).is_err());
+}
+
+#[test]
+fn open_hands_params() {
+    let players: Vec<PlayerInfo> = ["a", "b", "c"]
+        .into_iter()
+        .map(|id| PlayerInfo::human(id.into()))
+        .collect();
+    let params = r#"{"rules": {"open_hands": true, "public_stack": true}}"#;
+    let game: StreckeAPI = GameAPI::init(&players, Some(params)).unwrap();
+    let view: serde_json::Value =
+        serde_json::from_str(&game.player_view("a").unwrap()).unwrap();
+    let mut others: Vec<_> = view["other_hands"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["player"].as_str().unwrap())
+        .collect();
+    // Hands come in turn order, which depends on the random seats.
+    others.sort_unstable();
+    assert_eq!(others, ["b", "c"]);
+    let upcoming = view["upcoming_tiles"].as_array().unwrap();
+    assert_eq!(upcoming.len(), crate::tiles::all_tiles().len() - 3 * 3);
+}
+
+#[test]
+fn draft_params() {
+    let players = vec![
+        PlayerInfo::human("foo".into()),
+        PlayerInfo::ai("bot".into(), 1),
+    ];
+    let params = r#"{"rules": {"draft": true}}"#;
+    let mut game: StreckeAPI = GameAPI::init(&players, Some(params)).unwrap();
+    game.start(1, |_, _| {}).unwrap();
+    let view: serde_json::Value =
+        serde_json::from_str(&game.player_view("foo").unwrap()).unwrap();
+    assert!(!view["draft_pool"].as_array().unwrap().is_empty());
+    let play = r#"{"tile_idx": 0, "facing": "North"}"#;
+    assert!(game.process_action(play, |_, _| {}).is_err());
+
+    let mut num_picks = 0;
+    while game.state.is_drafting() {
+        game.process_action(r#"{"draft": 0}"#, |id, _| assert_eq!(id, "foo"))
+            .unwrap();
+        num_picks += 1;
+    }
+    assert_eq!(num_picks, 3);
+    assert_eq!(game.state.get_player("foo").unwrap().tiles_in_hand.len(), 3);
+    assert!(game.process_action(r#"{"draft": 0}"#, |_, _| {}).is_err());
+}
+
+#[test]
+fn resign_action() {
+    let players = vec![
+        PlayerInfo::human("foo".into()),
+        PlayerInfo::human("bar".into()),
+        PlayerInfo::human("baz".into()),
+    ];
+    let mut game: StreckeAPI = GameAPI::init(&players, None).unwrap();
+    let first = game.current_player_id().to_owned();
+    // Nobody can resign on someone else's behalf.
+    let other = players.iter().find(|p| p.id != first).unwrap();
+    let resign_other = format!(r#"{{"resign": "{}"}}"#, other.id);
+    assert!(game.process_action(&resign_other, |_, _| {}).is_err());
+    assert!(game.state.get_player(&other.id).is_some());
+
+    let mut notices = Vec::new();
+    game.process_action(r#"{"resign": true}"#, |id, msg| {
+        notices.push((id.to_owned(), msg.to_owned()));
+    })
+    .unwrap();
+    assert_eq!(notices.len(), 3);
+    assert!(notices[0].1.contains(r#""action":"resign""#));
+    assert!(game.state.get_player(&first).is_none());
+    assert_ne!(game.current_player_id(), first);
+
+    let second = game.current_player_id().to_owned();
+    game.process_action(r#"{"resign": true}"#, |_, _| {})
+        .unwrap();
+    assert!(game.is_game_over());
+    let winner = players
+        .iter()
+        .find(|p| p.id != first && p.id != second)
+        .unwrap();
+    assert_eq!(game.state.result().unwrap().winners(), [winner.id.as_str()]);
+}
+
+#[test]
+fn restore_keeps_scores() {
+    let players: Vec<PlayerInfo> = ["a", "b", "c", "d"]
+        .into_iter()
+        .map(|id| PlayerInfo::human(id.into()))
+        .collect();
+    let params = r#"{
+        "rules": {
+            "teams": [0, 1, 0, 1],
+            "scoring": {"LongestPath": {"survival_bonus": 3}}
+        },
+        "score_weights": {"per_player_outlasted": 500, "per_path": 2}
+    }"#;
+    let mut game: StreckeAPI = GameAPI::init(&players, Some(params)).unwrap();
+    while !game.is_game_over() {
+        let action = r#"{"tile_idx": 0, "facing": "North"}"#;
+        game.process_action(action, |_, _| {}).unwrap();
+    }
+    let final_state = game.final_state().unwrap();
+    let restored = StreckeAPI::restore(&players, &final_state).unwrap();
+    assert!(restored.is_game_over());
+    assert_eq!(restored.player_scores(), game.player_scores());
+    assert_eq!(restored.state.standings(), game.state.standings());
+
+    // Saves of just the board still load.
+    let board = serde_json::to_string(&game.state.board).unwrap();
+    let restored = StreckeAPI::restore(&players, &board).unwrap();
+    assert_eq!(restored.state.board.players, game.state.board.players);
+}
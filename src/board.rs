@@ -1,7 +1,8 @@
-use crate::tiles::{Direction, Port, Tile};
+use crate::tiles::{Direction, Effect, Port, Tile};
+use rand::seq::{IndexedRandom, SliceRandom};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Position {
     pub row: i8,
     pub col: i8,
@@ -11,6 +12,10 @@ pub struct Position {
 
 impl Position {
     pub fn is_valid_start(&self) -> bool {
+        self.is_edge_start() || self.is_interior_start()
+    }
+    // Edge seats sit just outside the board, facing in.
+    fn is_edge_start(&self) -> bool {
         if self.col < -1 || self.col > 6 || !self.alive {
             return false;
         }
@@ -21,7 +26,23 @@ impl Position {
             Port::G | Port::H => self.col == 6,
         }
     }
-    fn next_tile_coords(&self) -> (i8, i8) {
+    // Interior seats sit on the line between two cells, facing one of them.
+    pub fn is_interior_start(&self) -> bool {
+        let on_board = |(row, col): (i8, i8)| {
+            (0..6).contains(&row) && (0..6).contains(&col)
+        };
+        self.alive
+            && on_board((self.row, self.col))
+            && on_board(self.next_tile_coords())
+    }
+    // Whether both are the same point, seen from either side.
+    pub fn same_point(&self, other: &Position) -> bool {
+        let flipped = self.next_tile_position();
+        (self.row, self.col, self.port) == (other.row, other.col, other.port)
+            || (flipped.row, flipped.col, flipped.port)
+                == (other.row, other.col, other.port)
+    }
+    pub(crate) fn next_tile_coords(&self) -> (i8, i8) {
         match self.port.facing_side() {
             Direction::North => (self.row - 1, self.col),
             Direction::South => (self.row + 1, self.col),
@@ -45,10 +66,90 @@ impl Position {
 pub type EdgePos = i8;
 pub const NOT_READY: EdgePos = 48;
 
+// By default, players can't share a starting port or start right next to
+// each other.
+pub const MIN_START_SPACING: EdgePos = 2;
+// No two seats can be further apart than halfway round the board.
+pub const MAX_START_SPACING: EdgePos = NOT_READY / 2;
+
 pub fn is_valid_edge_position(pos: EdgePos) -> bool {
     (0..=NOT_READY).contains(&pos)
 }
 
+// Steps between two edge positions, going whichever way round is shorter.
+pub fn edge_distance(a: EdgePos, b: EdgePos) -> EdgePos {
+    let d = (a - b).rem_euclid(NOT_READY);
+    d.min(NOT_READY - d)
+}
+
+// Picks `count` more starting positions, each as far as possible from the
+// ones already taken. Ties are broken at random. Returns None if a seat would
+// end up closer than `min_spacing` to another.
+pub fn spread_seats(
+    taken: &[EdgePos],
+    count: usize,
+    min_spacing: EdgePos,
+    rng: &mut impl rand::Rng,
+) -> Option<Vec<EdgePos>> {
+    let mut seats = taken.to_vec();
+    for _ in 0..count {
+        let gap = |pos: EdgePos| {
+            seats
+                .iter()
+                .map(|&seat| edge_distance(pos, seat))
+                .min()
+                .unwrap_or(NOT_READY)
+        };
+        let widest = (0..NOT_READY).map(gap).max().unwrap();
+        if widest < min_spacing {
+            return None;
+        }
+        let choices: Vec<EdgePos> =
+            (0..NOT_READY).filter(|&pos| gap(pos) == widest).collect();
+        seats.push(*choices.choose(rng).unwrap());
+    }
+    Some(seats.split_off(taken.len()))
+}
+
+// Picks `count` random starting positions at least `min_spacing` apart. If
+// they don't all fit, returns as many as do. With no spacing at all, players
+// may share a port.
+pub fn random_seats(
+    count: usize,
+    min_spacing: EdgePos,
+    rng: &mut impl rand::Rng,
+) -> Vec<EdgePos> {
+    if min_spacing == 0 {
+        return (0..count).map(|_| rng.random_range(0..NOT_READY)).collect();
+    }
+    let mut positions: Vec<EdgePos> = (0..NOT_READY).collect();
+    positions.shuffle(rng);
+    let mut seats: Vec<EdgePos> = Vec::with_capacity(count);
+    for pos in positions {
+        if seats.len() == count {
+            break;
+        }
+        if seats.iter().all(|&s| edge_distance(pos, s) >= min_spacing) {
+            seats.push(pos);
+        }
+    }
+    seats
+}
+
+// Every starting position has a seat number: edge seats keep their edge
+// index, and interior seats follow in reading order.
+pub const NUM_SEATS: usize = NOT_READY as usize + 6 * 6 * 8;
+
+pub fn seat_index(pos: &Position) -> Option<usize> {
+    if let Some(seat) = edge_index(pos) {
+        return Some(seat as usize);
+    }
+    pos.is_interior_start().then(|| {
+        let cell = pos.row as usize * 6 + pos.col as usize;
+        NOT_READY as usize + cell * 8 + pos.port as usize
+    })
+}
+
 pub fn edge_position(pos: EdgePos) -> Position {
     let port: Port;
     let (row, col) = if pos < 12 {
@@ -74,20 +175,162 @@ pub fn edge_position(pos: EdgePos) -> Position {
     }
 }
 
+// The inverse of edge_position, for positions just outside the board.
+pub fn edge_index(pos: &Position) -> Option<EdgePos> {
+    let (row, col) = (pos.row, pos.col);
+    let on_side = |i: i8| (0..6).contains(&i);
+    match pos.port {
+        Port::F | Port::E if row == -1 && on_side(col) => {
+            Some(col * 2 + (pos.port == Port::E) as i8)
+        }
+        Port::H | Port::G if col == 6 && on_side(row) => {
+            Some(12 + row * 2 + (pos.port == Port::G) as i8)
+        }
+        Port::B | Port::A if row == 6 && on_side(col) => {
+            Some(34 - col * 2 + (pos.port == Port::A) as i8)
+        }
+        Port::D | Port::C if col == -1 && on_side(row) => {
+            Some(46 - row * 2 + (pos.port == Port::C) as i8)
+        }
+        _ => None,
+    }
+}
+
 #[test]
-fn test_is_valid_start() {
+fn test_edge_index() {
+    for pos in 0..NOT_READY {
+        assert_eq!(edge_index(&edge_position(pos)), Some(pos));
+    }
+    let inside = Position {
+        row: 2,
+        col: 3,
+        port: Port::A,
+        alive: true,
+    };
+    assert_eq!(edge_index(&inside), None);
+}
+
+#[test]
+fn test_start_spacing() {
+    assert_eq!(edge_distance(0, 47), 1);
+    assert_eq!(edge_distance(10, 34), 24);
+    let mut b = Board::default();
+    b.add_player(edge_position(10), MIN_START_SPACING).unwrap();
+    assert!(b.add_player(edge_position(10), MIN_START_SPACING).is_err());
+    assert!(b.add_player(edge_position(11), MIN_START_SPACING).is_err());
+    assert!(b.add_player(edge_position(12), MIN_START_SPACING).is_ok());
+    assert!(b.add_player(edge_position(11), 0).is_ok());
     assert!(
-        Position {
-            row: -1,
-            col: 2,
-            port: Port::E,
-            alive: true
-        }
-        .is_valid_start()
+        b.add_player(edge_position(40), MAX_START_SPACING + 1)
+            .is_err()
     );
 }
 
-#[derive(Default, Debug, Deserialize, Serialize)]
+#[test]
+fn test_spread_seats() {
+    let mut rng = rand::rng();
+    assert_eq!(spread_seats(&[5], 1, 0, &mut rng), Some(vec![29]));
+    let seats = spread_seats(&[], 4, MIN_START_SPACING, &mut rng).unwrap();
+    for (i, &a) in seats.iter().enumerate() {
+        for &b in &seats[..i] {
+            assert!(edge_distance(a, b) >= 12);
+        }
+    }
+    // Only one more seat fits a quarter of the way round from both of these.
+    assert!(spread_seats(&[0, 18], 1, 12, &mut rng).is_some());
+    assert!(spread_seats(&[0, 18], 2, 12, &mut rng).is_none());
+}
+
+#[test]
+fn test_random_seats() {
+    let mut rng = rand::rng();
+    let seats = random_seats(11, MIN_START_SPACING, &mut rng);
+    assert_eq!(seats.len(), 11);
+    for (i, &a) in seats.iter().enumerate() {
+        for &b in &seats[..i] {
+            assert!(edge_distance(a, b) >= MIN_START_SPACING);
+        }
+    }
+    // At most four seats fit a quarter of the way round apart.
+    assert!(random_seats(5, 12, &mut rng).len() <= 4);
+    // Without spacing, there's room for more players than ports.
+    assert_eq!(random_seats(60, 0, &mut rng).len(), 60);
+}
+
+#[test]
+fn test_is_valid_start() {
+    let pos = |row, col, port| Position {
+        row,
+        col,
+        port,
+        alive: true,
+    };
+    assert!(pos(-1, 2, Port::E).is_valid_start());
+    assert!(!pos(-1, 2, Port::A).is_valid_start());
+    // Between (2, 3) and (3, 3), facing down or up.
+    assert!(pos(2, 3, Port::E).is_interior_start());
+    assert!(pos(3, 3, Port::B).is_valid_start());
+    assert!(pos(2, 3, Port::E).same_point(&pos(3, 3, Port::B)));
+    // Facing off the board.
+    assert!(!pos(5, 3, Port::E).is_valid_start());
+    assert!(!pos(-1, 2, Port::E).is_interior_start());
+
+    let mut seats: Vec<usize> = (0..NOT_READY)
+        .map(|i| seat_index(&edge_position(i)).unwrap())
+        .collect();
+    for row in 0..6 {
+        for col in 0..6 {
+            for port in [Port::A, Port::C, Port::E, Port::G] {
+                seats.extend(seat_index(&pos(row, col, port)));
+            }
+        }
+    }
+    seats.sort();
+    seats.dedup();
+    assert!(seats.len() < NUM_SEATS);
+    assert!(seats.iter().all(|&s| s < NUM_SEATS));
+}
+
+#[test]
+fn test_interior_start() {
+    use crate::tiles::all_tiles;
+    let mut b = Board::default();
+    let start = Position {
+        row: 2,
+        col: 3,
+        port: Port::E,
+        alive: true,
+    };
+    let p0 = b.add_player(start.clone(), MIN_START_SPACING).unwrap();
+    let facing_up = Position {
+        row: 3,
+        col: 3,
+        port: Port::B,
+        alive: true,
+    };
+    assert!(b.add_player(facing_up.clone(), MIN_START_SPACING).is_err());
+    assert!(b.add_player(facing_up, 0).is_ok());
+    // The first tile goes in the cell in front, and the trail runs on from
+    // the start.
+    b.play_tile(p0, &all_tiles()[0], Direction::North);
+    assert!(b.grid[3][3].is_some());
+    assert_eq!(b.players[p0][0], start);
+    assert!(b.players[p0].len() > 1);
+
+    // Four curves make a loop that leads straight back to the start.
+    let curve = all_tiles()[4];
+    let mut b = Board::default();
+    let p0 = b.add_player(start.clone(), MIN_START_SPACING).unwrap();
+    for (row, col) in [(2, 3), (2, 4), (3, 4)] {
+        b.grid[row][col] = Some((curve, Direction::North));
+    }
+    b.play_tile(p0, &curve, Direction::North);
+    let end = b.players[p0].last().unwrap();
+    assert!(!end.alive);
+    assert_eq!((end.row, end.col, end.port), (2, 3, Port::E));
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
 pub struct Board {
     // 2d array of tiles and their orientations
     grid: [[Option<(Tile, Direction)>; 6]; 6],
@@ -105,57 +348,137 @@ impl Board {
         }
         Some(&self.grid[pos.row as usize][pos.col as usize])
     }
-    pub fn add_player(&mut self, pos: Position) -> Result<usize, String> {
+    // Adds a player at least `min_spacing` edge positions away from everyone
+    // else's start.
+    pub fn add_player(
+        &mut self,
+        pos: Position,
+        min_spacing: EdgePos,
+    ) -> Result<usize, String> {
+        if !(0..=MAX_START_SPACING).contains(&min_spacing) {
+            return Err(format!("Invalid start spacing: {}", min_spacing));
+        }
         if !pos.is_valid_start() {
             return Err(format!("Invalid starting position: {:?}", pos));
         }
+        let shared = self.players.iter().any(|trail| trail[0].same_point(&pos));
+        if shared && min_spacing > 0 {
+            return Err("Another player already starts there".to_owned());
+        }
+        // Spacing is measured round the edge, so it only applies between
+        // edge seats.
+        if let Some(seat) = edge_index(&pos) {
+            let too_close = self
+                .players
+                .iter()
+                .filter_map(|trail| edge_index(&trail[0]))
+                .any(|other| edge_distance(seat, other) < min_spacing);
+            if too_close {
+                return Err(format!(
+                    "Starting position {} is too close to another player",
+                    seat
+                ));
+            }
+        }
         self.players.push(vec![pos]);
         Ok(self.players.len() - 1)
     }
+    // Whether a spinner at (row, col) can turn the tile on its `dir` side.
+    // Bridges can't be turned.
+    pub fn can_spin(&self, row: i8, col: i8, dir: Direction) -> bool {
+        let (d_row, d_col) = dir.grid_offsets();
+        let pos = Position {
+            row: row + d_row,
+            col: col + d_col,
+            port: Port::A,
+            alive: true,
+        };
+        matches!(
+            self.get_tile(&pos),
+            Some(Some((tile, _))) if tile.effect != Some(Effect::Bridge)
+        )
+    }
     pub fn play_tile(
         &mut self,
         player_idx: usize,
         tile: &Tile,
         facing: Direction,
+    ) {
+        self.play_tile_with_spin(player_idx, tile, facing, None);
+    }
+    // Plays a tile, and if it's a spinner, turns the tile on its `spin` side
+    // a quarter turn clockwise.
+    pub fn play_tile_with_spin(
+        &mut self,
+        player_idx: usize,
+        tile: &Tile,
+        facing: Direction,
+        spin: Option<Direction>,
     ) {
         // Add the new tile in the target location.
         if let Some(pos) = self.players[player_idx].last() {
             let (row, col) = pos.next_tile_coords();
             self.grid[row as usize][col as usize] = Some((*tile, facing));
+            if let Some(dir) = spin
+                && tile.effect == Some(Effect::Spinner)
+                && self.can_spin(row, col, dir)
+            {
+                let (d_row, d_col) = dir.grid_offsets();
+                let (row, col) =
+                    ((row + d_row) as usize, (col + d_col) as usize);
+                // Trails that already crossed it keep the paths they took.
+                // Only trails that reach it from now on take the new ones.
+                if let Some((_, facing)) = &mut self.grid[row][col] {
+                    *facing = facing.clockwise();
+                }
+            }
         }
         // Move all players, if still alive.
         for trail in self.players.iter_mut() {
-            while let Some(pos) = trail.last() {
-                if !pos.alive {
-                    break;
-                }
-                let (d_row, d_col) = pos.port.facing_side().grid_offsets();
-                let row = pos.row + d_row;
-                let col = pos.col + d_col;
-                if !(0..6).contains(&row) || !(0..6).contains(&col) {
-                    let port = pos.port.flip();
+            Self::follow_trail(&self.grid, trail);
+        }
+    }
+    // Moves a player along the paths in front of them until they reach an
+    // empty cell or fall off the board.
+    fn follow_trail(
+        grid: &[[Option<(Tile, Direction)>; 6]; 6],
+        trail: &mut Vec<Position>,
+    ) {
+        while let Some(pos) = trail.last() {
+            if !pos.alive {
+                break;
+            }
+            let (d_row, d_col) = pos.port.facing_side().grid_offsets();
+            let row = pos.row + d_row;
+            let col = pos.col + d_col;
+            if !(0..6).contains(&row) || !(0..6).contains(&col) {
+                let port = pos.port.flip();
+                trail.push(Position {
+                    row,
+                    col,
+                    port,
+                    alive: false,
+                });
+                break;
+            }
+            match grid[row as usize][col as usize] {
+                // Hit another tile, traverse and keep looping.
+                Some((t, facing)) => {
+                    let port = t.traverse(pos.port.flip(), facing);
+                    // Only a trail from an interior seat can come back round
+                    // to its start, and then it would go round forever.
+                    let start = &trail[0];
+                    let looped =
+                        (start.row, start.col, start.port) == (row, col, port);
                     trail.push(Position {
                         row,
                         col,
                         port,
-                        alive: false,
+                        alive: !looped,
                     });
-                    break;
-                }
-                match self.grid[row as usize][col as usize] {
-                    // Hit another tile, traverse and keep looping.
-                    Some((t, facing)) => {
-                        let port = t.traverse(pos.port.flip(), facing);
-                        trail.push(Position {
-                            row,
-                            col,
-                            port,
-                            alive: true,
-                        });
-                    }
-                    // Hit a blank cell, stop iterating.
-                    None => break,
                 }
+                // Hit a blank cell, stop iterating.
+                None => break,
             }
         }
     }
@@ -172,15 +495,85 @@ fn test_add_players() {
     let mut b = Board::default();
     assert_eq!(b.players.len(), 0);
     assert_eq!(
-        b.add_player(Position {
-            row: 1,
-            col: -1,
-            port: Port::D,
-            alive: true,
-        }),
+        b.add_player(
+            Position {
+                row: 1,
+                col: -1,
+                port: Port::D,
+                alive: true,
+            },
+            MIN_START_SPACING
+        ),
         Ok(0)
     );
     assert_eq!(b.players.len(), 1);
     assert_eq!(b.players[0].len(), 1);
     assert_eq!(b.players[0][0].port, Port::D);
 }
+
+#[test]
+fn test_bridge() {
+    use crate::tiles::{all_tiles, power_tiles};
+    // Two players face the same cell from above and below. The one above
+    // plays a tile that takes them down to where the other one is waiting.
+    let play = |tile: &Tile| {
+        let mut b = Board::default();
+        for (row, port) in [(0, Port::F), (2, Port::B)] {
+            let pos = Position {
+                row,
+                col: 2,
+                port,
+                alive: true,
+            };
+            b.add_player(pos, MIN_START_SPACING).unwrap();
+        }
+        b.play_tile(0, tile, Direction::North);
+        let ends: Vec<_> = b
+            .players
+            .iter()
+            .map(|trail| trail.last().unwrap().clone())
+            .collect();
+        assert!(ends.iter().all(|pos| pos.alive));
+        assert_eq!(ends[0].port, Port::E);
+        ends[1].port
+    };
+    // On a plain tile the second player runs back up the first one's trail,
+    // to where they started.
+    assert_eq!(play(&all_tiles()[0]), Port::A);
+    // A bridge takes them up the other lane instead.
+    assert_eq!(play(&power_tiles()[3]), Port::B);
+}
+
+#[test]
+fn test_spinner() {
+    use crate::tiles::{all_tiles, power_tiles};
+    let mut b = Board::default();
+    let p0 = b.add_player(edge_position(0), MIN_START_SPACING).unwrap();
+    b.play_tile(p0, &all_tiles()[5], Direction::North);
+    assert_eq!(b.players[p0].last().unwrap().port, Port::E);
+    let before = b.players[p0].clone();
+    b.grid[1][1] = Some((power_tiles()[3], Direction::North));
+    // A spinner at (1, 0) can turn the tile above it but not the bridge.
+    assert!(b.can_spin(1, 0, Direction::North));
+    assert!(!b.can_spin(1, 0, Direction::East));
+    assert!(!b.can_spin(1, 0, Direction::South));
+    b.play_tile_with_spin(
+        p0,
+        &power_tiles()[0],
+        Direction::North,
+        Some(Direction::North),
+    );
+    assert_eq!(b.grid[0][0].unwrap().1, Direction::East);
+    assert_eq!(b.grid[1][0].unwrap().1, Direction::North);
+    // The trail through the turned tile stays as it was, and carries on
+    // into the spinner.
+    let trail = &b.players[p0];
+    assert_eq!(trail[..2], before[..]);
+    assert_eq!((trail[2].row, trail[2].col), (1, 0));
+    // Without a choice, nothing turns.
+    let mut b = Board::default();
+    let p0 = b.add_player(edge_position(0), MIN_START_SPACING).unwrap();
+    b.play_tile(p0, &all_tiles()[5], Direction::North);
+    b.play_tile(p0, &power_tiles()[0], Direction::North);
+    assert_eq!(b.grid[0][0].unwrap().1, Direction::North);
+}
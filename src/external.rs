@@ -0,0 +1,295 @@
+// Agents that run as a separate process, so bots can be written in any
+// language. Each turn, the process receives one line of JSON on stdin:
+//
+//   {"id": 7, "observation": {...}, "time_budget_ms": 500}
+//
+// and must reply with one line of JSON on stdout, echoing the id:
+//
+//   {"id": 7, "tile_idx": 0, "facing": "North"}
+//
+// Replies that are late, malformed, or illegal are replaced by a move from
+// AvoidSuddenDeathAgent. Late replies to earlier requests are skipped, since
+// their id doesn't match.
+use crate::agent::{Agent, AvoidSuddenDeathAgent};
+use crate::game::PlayerObservation;
+use crate::tiles::Direction;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Instant;
+
+#[derive(Serialize)]
+struct Request<'a> {
+    id: u64,
+    observation: &'a PlayerObservation,
+    time_budget_ms: u128,
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    id: u64,
+    tile_idx: usize,
+    facing: Direction,
+}
+
+pub struct ExternalAgent {
+    child: Child,
+    // Lines for a background thread to write to the child's stdin, so a
+    // child that stops reading can't hold up the game.
+    requests: Sender<String>,
+    // Lines read from the child's stdout by a background thread.
+    replies: Mutex<Receiver<String>>,
+    next_id: AtomicU64,
+}
+
+impl ExternalAgent {
+    // Starts an agent from a command line, split into words the way a shell
+    // would, quotes and backslashes included. Nothing else is expanded.
+    pub fn spawn(command_line: &str) -> io::Result<Self> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidInput, msg);
+        let words = split_command_line(command_line).map_err(invalid)?;
+        let (program, args) = words
+            .split_first()
+            .ok_or_else(|| invalid("Empty agent command".to_owned()))?;
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        Self::from_command(cmd)
+    }
+    pub fn from_command(mut cmd: Command) -> io::Result<Self> {
+        let mut child =
+            cmd.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (requests, pending) = mpsc::channel::<String>();
+        thread::spawn(move || {
+            for msg in pending {
+                let written = writeln!(stdin, "{}", msg);
+                if written.and_then(|_| stdin.flush()).is_err() {
+                    break;
+                }
+            }
+        });
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            child,
+            requests,
+            replies: Mutex::new(rx),
+            next_id: AtomicU64::new(0),
+        })
+    }
+    fn request_action(
+        &self,
+        obs: &PlayerObservation,
+        deadline: Instant,
+    ) -> Result<(usize, Direction), String> {
+        let replies = self.replies.lock().unwrap();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let budget = deadline.saturating_duration_since(Instant::now());
+        let request = Request {
+            id,
+            observation: obs,
+            time_budget_ms: budget.as_millis(),
+        };
+        let msg = serde_json::to_string(&request).map_err(|e| e.to_string())?;
+        self.requests
+            .send(msg)
+            .map_err(|_| "Agent stopped reading".to_owned())?;
+        let reply = loop {
+            let budget = deadline.saturating_duration_since(Instant::now());
+            let line = replies.recv_timeout(budget).map_err(|e| match e {
+                RecvTimeoutError::Timeout => "No reply in time".to_owned(),
+                RecvTimeoutError::Disconnected => "Agent exited".to_owned(),
+            })?;
+            let reply: Reply = serde_json::from_str(&line)
+                .map_err(|e| format!("Bad reply {:?}: {}", line, e))?;
+            // Any other id answers a request that already timed out.
+            if reply.id == id {
+                break reply;
+            }
+        };
+        if reply.tile_idx >= obs.hand.len() {
+            return Err(format!("Illegal tile index: {}", reply.tile_idx));
+        }
+        Ok((reply.tile_idx, reply.facing))
+    }
+}
+
+// Splits a command line into words at whitespace. Single quotes keep
+// everything inside them as is; in double quotes and elsewhere, a backslash
+// keeps the next character as is.
+fn split_command_line(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\\' => {
+                let next = chars.next().ok_or("Trailing backslash")?;
+                word.get_or_insert_default().push(next);
+            }
+            '\'' | '"' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        None => return Err(format!("Unmatched {}", c)),
+                        Some(end) if end == c => break,
+                        Some('\\') if c == '"' => {
+                            let next =
+                                chars.next().ok_or("Trailing backslash")?;
+                            word.push(next);
+                        }
+                        Some(other) => word.push(other),
+                    }
+                }
+            }
+            c => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+impl Agent for ExternalAgent {
+    fn choose_action(
+        &self,
+        obs: &PlayerObservation,
+        deadline: Instant,
+    ) -> (usize, Direction) {
+        match self.request_action(obs, deadline) {
+            Ok(action) => action,
+            Err(e) => {
+                warn!(
+                    "Player {}: External agent failed, using fallback: {}",
+                    obs.board_index, e
+                );
+                AvoidSuddenDeathAgent.choose_action(obs, deadline)
+            }
+        }
+    }
+}
+
+impl Drop for ExternalAgent {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+    use crate::board::edge_position;
+    use crate::game::GameManager;
+    use std::time::Duration;
+
+    fn observation() -> PlayerObservation {
+        let mut game = GameManager::new(&mut rand::rng());
+        game.register_player("foo".into(), edge_position(5))
+            .unwrap();
+        game.current_observation()
+    }
+
+    fn shell_agent(script: &str) -> ExternalAgent {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", script]);
+        ExternalAgent::from_command(cmd).unwrap()
+    }
+
+    // Replies to every request with the given move, echoing its id.
+    fn reply_script(tile_idx: usize, facing: &str) -> String {
+        format!(
+            r#"while read line; do
+                id=$(echo "$line" | sed -E 's/^\{{"id":([0-9]+).*/\1/')
+                echo "{{\"id\": $id, \"tile_idx\": {}, \"facing\": \"{}\"}}"
+            done"#,
+            tile_idx, facing
+        )
+    }
+
+    #[test]
+    fn test_external_agent_reply() {
+        let agent = shell_agent(&reply_script(2, "East"));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let action = agent.choose_action(&observation(), deadline);
+        assert_eq!(action, (2, Direction::East));
+    }
+
+    #[test]
+    fn test_external_agent_fallbacks() {
+        let obs = observation();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let expected = AvoidSuddenDeathAgent.choose_action(&obs, deadline);
+        // Illegal tile index.
+        let agent = shell_agent(&reply_script(7, "East"));
+        assert_eq!(agent.choose_action(&obs, deadline), expected);
+        // A reply without the request's id.
+        let agent = shell_agent(
+            r#"while read line; do echo '{"tile_idx": 2, "facing": "East"}'; done"#,
+        );
+        assert_eq!(agent.choose_action(&obs, deadline), expected);
+        // Malformed reply.
+        let agent = shell_agent("cat");
+        assert_eq!(agent.choose_action(&obs, deadline), expected);
+        // No reply before the deadline.
+        let agent = shell_agent("sleep 10");
+        let deadline = Instant::now() + Duration::from_millis(50);
+        assert_eq!(agent.choose_action(&obs, deadline), expected);
+    }
+
+    #[test]
+    fn test_late_reply_is_skipped() {
+        let obs = observation();
+        // Answers the first request too late, just as the second one comes.
+        let agent = shell_agent(
+            r#"read line; sleep 0.3
+            echo '{"id": 0, "tile_idx": 1, "facing": "West"}'
+            read line; echo '{"id": 1, "tile_idx": 2, "facing": "East"}'
+            sleep 10"#,
+        );
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let expected = AvoidSuddenDeathAgent.choose_action(&obs, deadline);
+        assert_eq!(agent.choose_action(&obs, deadline), expected);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        assert_eq!(agent.choose_action(&obs, deadline), (2, Direction::East));
+    }
+
+    #[test]
+    fn test_agent_that_stops_reading() {
+        // Enough requests to fill the pipe, and each one still gives up on
+        // time.
+        let obs = observation();
+        let agent = shell_agent("sleep 10");
+        let start = Instant::now();
+        for _ in 0..100 {
+            let deadline = Instant::now() + Duration::from_millis(2);
+            agent.choose_action(&obs, deadline);
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_split_command_line() {
+        let line = r#"python3 "my bot.py" "" --name 'a "b"' c\ d"#;
+        let words = split_command_line(line).unwrap();
+        assert_eq!(
+            words,
+            ["python3", "my bot.py", "", "--name", "a \"b\"", "c d"]
+        );
+        assert!(split_command_line("bot 'oops").is_err());
+        assert!(split_command_line("  ").unwrap().is_empty());
+    }
+}
@@ -1,114 +1,813 @@
-use crate::board::{Board, Position};
-use crate::tiles::{Direction, Tile, all_tiles};
+use crate::agent::create_agent;
+use crate::board::{
+    Board, EdgePos, MAX_START_SPACING, MIN_START_SPACING, NUM_SEATS, Position,
+    seat_index,
+};
+use crate::puzzle::Puzzle;
+use crate::rules::{Rules, Scoring};
+use crate::tiles::{Direction, Effect, Tile, all_tiles, power_tiles};
 use log::info;
 use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
 use std::cmp;
+use std::time::{Duration, Instant};
 
 // How large each player's "hand" can be.
 const TILES_PER_PLAYER: i32 = 3;
+// The most paths a trail can follow: every path on a full board. A trail
+// never follows the same path twice.
+const MAX_PATH_LENGTH: i32 = 36 * 4;
+// How long the agent behind a hint may think.
+const HINT_TIME_BUDGET: Duration = Duration::from_millis(200);
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Player {
     pub username: String,
-    board_index: usize,
+    pub(crate) board_index: usize,
     pub tiles_in_hand: Vec<Tile>,
 }
 
+// Public information about another player in the game.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OpponentInfo {
+    pub board_index: usize,
+    pub hand_size: usize,
+    // Their tiles, if the rules let this player see them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hand: Option<Vec<Tile>>,
+}
+
+// Everything a single player is allowed to know about the game state.
+// Agents only ever see this, so they can't peek at other players' hands or
+// the order of the tile stack unless the rules make them public.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlayerObservation {
+    pub board: Board,
+    pub board_index: usize,
+    pub hand: Vec<Tile>,
+    // Number of tiles left in the stack.
+    pub tiles_remaining: usize,
+    // Other alive players, in turn order starting after this player.
+    pub opponents: Vec<OpponentInfo>,
+    // Board index of the player holding the dragon tile, if any.
+    pub dragon_holder: Option<usize>,
+    // The tiles left in the stack, next draw first, if the stack is public.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upcoming_tiles: Option<Vec<Tile>>,
+    // Face-up tiles still to be drafted, before play begins.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub draft_pool: Vec<Tile>,
+}
+
+impl PlayerObservation {
+    pub fn position(&self) -> &Position {
+        self.board.players[self.board_index].last().unwrap()
+    }
+}
+
+// A recommended move for a player, with a short human-readable reason.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Suggestion {
+    pub tile_idx: usize,
+    pub facing: Direction,
+    pub explanation: String,
+}
+
+// A single turn, as passed to GameManager::take_turn.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct Move {
+    // Board index of the player who took the turn.
+    pub board_index: usize,
+    pub tile_idx: usize,
+    pub facing: Direction,
+    // Which neighbour a spinner turned, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spin: Option<Direction>,
+}
+
+// How a finished game turned out.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "outcome")]
+pub enum GameResult {
+    // The last player standing, or the solver of a puzzle.
+    Winner { player: String },
+    // Everyone still alive when the last tile was played.
+    SharedWin { players: Vec<String> },
+    // Everyone left was eliminated by the same move. Failed puzzles end this
+    // way too.
+    NoWinner,
+}
+
+impl GameResult {
+    fn from_winners(mut players: Vec<String>) -> Self {
+        match players.len() {
+            0 => GameResult::NoWinner,
+            1 => GameResult::Winner {
+                player: players.pop().unwrap(),
+            },
+            _ => GameResult::SharedWin { players },
+        }
+    }
+    pub fn winners(&self) -> &[String] {
+        match self {
+            GameResult::Winner { player } => std::slice::from_ref(player),
+            GameResult::SharedWin { players } => players,
+            GameResult::NoWinner => &[],
+        }
+    }
+    pub fn is_winner(&self, username: &str) -> bool {
+        self.winners().iter().any(|w| w == username)
+    }
+}
+
+// How a player fared, for ranking everyone at the end of a game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Standing {
+    pub username: String,
+    pub board_index: usize,
+    // 1 for the survivors and their teammates, or whoever lasted longest, or
+    // whoever has the most points under longest-path scoring. Players who tie
+    // share a place, and the next place is skipped.
+    pub placement: usize,
+    // The move that knocked this player out, counting from 1. Players who
+    // were already out when a puzzle started were knocked out on move 0.
+    pub eliminated_on: Option<usize>,
+    // How many paths the player's trail followed.
+    pub path_length: usize,
+}
+
+// How standings are turned into scores, e.g. for ladder ratings. A player
+// always scores more than anyone placed below them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ScoreWeights {
+    // Points for each player placed below this one.
+    pub per_player_outlasted: i32,
+    // Points for each path followed, which separates tied players.
+    pub per_path: i32,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        ScoreWeights {
+            per_player_outlasted: 1000,
+            per_path: 1,
+        }
+    }
+}
+
+impl ScoreWeights {
+    // Checks that path points can never outweigh placement, and that no
+    // score can overflow.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.per_path < 0 {
+            return Err("Path weight can't be negative".to_owned());
+        }
+        let max_path_points = self.per_path.saturating_mul(MAX_PATH_LENGTH);
+        if self.per_player_outlasted <= max_path_points {
+            return Err(format!(
+                "Placement weight must be over {} to outweigh paths",
+                max_path_points
+            ));
+        }
+        // Every seat taken, and the longest trail possible
+        let max_score = self
+            .per_player_outlasted
+            .checked_mul(NUM_SEATS as i32)
+            .and_then(|points| points.checked_add(max_path_points));
+        if max_score.is_none() {
+            return Err("Placement weight is too large".to_owned());
+        }
+        Ok(())
+    }
+    // Saturates rather than overflowing, for weights that weren't validated.
+    pub fn score(&self, standing: &Standing, outlasted: usize) -> i32 {
+        let outlasted = i32::try_from(outlasted).unwrap_or(i32::MAX);
+        let path_length =
+            i32::try_from(standing.path_length).unwrap_or(i32::MAX);
+        outlasted
+            .saturating_mul(self.per_player_outlasted)
+            .saturating_add(path_length.saturating_mul(self.per_path))
+    }
+}
+
+// Everything needed to replay a game from the beginning.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GameHistory {
+    // The shuffled tile stack, before any tiles were dealt.
+    pub tile_stack: Vec<Tile>,
+    // Usernames and starting positions, in the order players registered.
+    pub players: Vec<(String, Position)>,
+    pub moves: Vec<Move>,
+    // Set if the game started from a puzzle rather than an empty board.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub puzzle: Option<Puzzle>,
+    #[serde(default)]
+    pub rules: Rules,
+    // Indices into the draft pool, in the order tiles were picked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub draft_picks: Vec<usize>,
+    // Players who resigned or ran out of time: (moves played before, board
+    // index).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forfeits: Vec<(usize, usize)>,
+    // How far apart starting positions had to be. Older histories had no
+    // limit, so they default to zero.
+    #[serde(default)]
+    pub min_start_spacing: EdgePos,
+    // Set if the stack was supplied rather than shuffled by the game, in
+    // which case the rules never add or remove tiles from it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub custom_stack: bool,
+}
+
+#[derive(Clone)]
 pub struct GameManager {
     pub board: Board,
-    tile_stack: Vec<Tile>,
+    pub(crate) tile_stack: Vec<Tile>,
     pub alive_players: Vec<Player>,
     pub current_player_idx: usize,
-    dragon_player_bidx: Option<usize>,
+    pub(crate) dragon_player_bidx: Option<usize>,
+    history: GameHistory,
+    // Whether the puzzle was solved, once the solver has moved.
+    puzzle_solved: Option<bool>,
+    // Set once the game is over.
+    result: Option<GameResult>,
+    // The move that knocked out each player, by board index.
+    eliminated_on: Vec<Option<usize>>,
+    // Face-up tiles still to be drafted, before play begins.
+    draft_pool: Vec<Tile>,
+    // Set once the rules are, after which nobody else can join.
+    rules_set: bool,
 }
 
 impl GameManager {
     pub fn new(rng: &mut impl rand::Rng) -> Self {
         let mut tile_stack = all_tiles();
         tile_stack.shuffle(rng);
+        let mut game = Self::with_tile_stack(tile_stack);
+        game.history.custom_stack = false;
+        game
+    }
+    // Starts a game that will deal tiles from the end of the given stack.
+    pub fn with_tile_stack(tile_stack: Vec<Tile>) -> Self {
         GameManager {
             board: Board::default(),
+            history: GameHistory {
+                tile_stack: tile_stack.clone(),
+                players: Vec::new(),
+                moves: Vec::new(),
+                puzzle: None,
+                rules: Rules::default(),
+                draft_picks: Vec::new(),
+                forfeits: Vec::new(),
+                min_start_spacing: MIN_START_SPACING,
+                custom_stack: true,
+            },
             tile_stack,
             alive_players: Vec::new(),
             current_player_idx: 0,
             dragon_player_bidx: None,
+            puzzle_solved: None,
+            result: None,
+            eliminated_on: Vec::new(),
+            draft_pool: Vec::new(),
+            rules_set: false,
+        }
+    }
+    // Sets up a puzzle position, naming its players in turn order. The game
+    // ends as soon as the first player (the solver) has moved.
+    pub fn from_puzzle(
+        puzzle: &Puzzle,
+        usernames: Vec<String>,
+    ) -> Result<Self, String> {
+        if usernames.len() != puzzle.players.len() {
+            return Err(format!(
+                "Puzzle needs {} players, got {}",
+                puzzle.players.len(),
+                usernames.len()
+            ));
+        }
+        let mut game = Self::with_tile_stack(puzzle.tile_stack.clone());
+        game.board = puzzle.board.clone();
+        game.record_eliminations();
+        game.dragon_player_bidx = puzzle.dragon_holder;
+        for (username, p) in usernames.into_iter().zip(puzzle.players.iter()) {
+            let pos = game
+                .board
+                .players
+                .get(p.board_index)
+                .and_then(|trail| trail.last())
+                .filter(|pos| pos.alive)
+                .ok_or(format!("Invalid puzzle player {}", p.board_index))?;
+            game.history.players.push((username.clone(), pos.clone()));
+            game.alive_players.push(Player {
+                username,
+                board_index: p.board_index,
+                tiles_in_hand: p.hand.clone(),
+            });
         }
+        game.history.puzzle = Some(puzzle.clone());
+        Ok(game)
+    }
+    // Rebuilds a game from its history, stopping after the first `num_moves`
+    // moves have been played.
+    pub fn replay(
+        history: &GameHistory,
+        num_moves: usize,
+    ) -> Result<Self, String> {
+        let mut game = if let Some(puzzle) = &history.puzzle {
+            let names = history.players.iter().map(|(name, _)| name.clone());
+            Self::from_puzzle(puzzle, names.collect())?
+        } else {
+            let mut game = Self::with_tile_stack(history.tile_stack.clone());
+            game.history.custom_stack = history.custom_stack;
+            game.set_min_start_spacing(history.min_start_spacing)?;
+            for (username, pos) in history.players.iter() {
+                game.register_player(username.clone(), pos.clone())?;
+            }
+            game
+        };
+        game.set_rules(history.rules.clone())?;
+        for &pick in history.draft_picks.iter() {
+            game.draft_tile(pick)?;
+        }
+        for (i, m) in history.moves.iter().take(num_moves).enumerate() {
+            game.replay_forfeits(&history.forfeits, i)?;
+            if game.is_over() {
+                return Err("Too many moves in history".to_owned());
+            }
+            if game.current_player().board_index != m.board_index {
+                return Err(format!(
+                    "Move by player {} out of turn",
+                    m.board_index
+                ));
+            }
+            game.take_turn_with_spin(m.tile_idx, m.facing, m.spin)?;
+        }
+        game.replay_forfeits(&history.forfeits, num_moves)?;
+        Ok(game)
+    }
+    pub fn history(&self) -> &GameHistory {
+        &self.history
+    }
+    // The move that knocked out each player, by board index.
+    pub(crate) fn eliminations(&self) -> &[Option<usize>] {
+        &self.eliminated_on
+    }
+    // Puts back the board of a finished game and when everyone was knocked
+    // out, so it ranks players the same way it did when it ended.
+    pub(crate) fn restore_finished(
+        &mut self,
+        board: Board,
+        eliminated_on: Vec<Option<usize>>,
+    ) {
+        self.board = board;
+        self.eliminated_on = eliminated_on;
+        self.eliminated_on.resize(self.board.players.len(), None);
+    }
+    // The puzzle this game started from, if any.
+    pub fn puzzle(&self) -> Option<&Puzzle> {
+        self.history.puzzle.as_ref()
+    }
+    // Whether the puzzle was solved, or None if it's unfinished or this isn't
+    // a puzzle game.
+    pub fn puzzle_solved(&self) -> Option<bool> {
+        self.puzzle_solved
+    }
+    pub fn result(&self) -> Option<&GameResult> {
+        self.result.as_ref()
+    }
+    // Switches to different rules, once everyone has registered.
+    pub fn set_rules(&mut self, rules: Rules) -> Result<(), String> {
+        let drafted =
+            self.is_drafting() || !self.history.draft_picks.is_empty();
+        if drafted || !self.history.moves.is_empty() {
+            return Err("The game has already started".to_owned());
+        }
+        rules.validate(self.board.players.len())?;
+        if rules.draft {
+            if self.history.puzzle.is_some() {
+                return Err("Puzzles come with their hands dealt".to_owned());
+            }
+            // Everyone's starting tiles go face up for the draft.
+            for p in self.alive_players.iter_mut() {
+                self.draft_pool.append(&mut p.tiles_in_hand);
+            }
+        }
+        // Power tiles only ever sit in the undealt stack, so this undoes any
+        // earlier rules. Supplied stacks are played exactly as given.
+        if !self.history.custom_stack {
+            self.tile_stack.retain(|t| t.effect.is_none());
+        }
+        if rules.power_tiles && !self.history.custom_stack {
+            // Spread them evenly through the undealt stack, so they turn up
+            // all through the game and replays see them in the same places.
+            let power = power_tiles();
+            let spacing = self.tile_stack.len() / (power.len() + 1);
+            for (i, tile) in power.into_iter().enumerate().rev() {
+                self.tile_stack.insert((i + 1) * spacing, tile);
+            }
+        }
+        self.history.rules = rules;
+        self.rules_set = true;
+        Ok(())
+    }
+    pub fn is_drafting(&self) -> bool {
+        !self.draft_pool.is_empty()
+    }
+    pub fn draft_pool(&self) -> &[Tile] {
+        &self.draft_pool
+    }
+    // Moves a tile from the draft pool into the current player's hand. Picks
+    // go around the table in turn order, then back again, and so on.
+    pub fn draft_tile(&mut self, pool_idx: usize) -> Result<(), String> {
+        if !self.is_drafting() {
+            return Err("Nothing left to draft".to_owned());
+        }
+        if pool_idx >= self.draft_pool.len() {
+            return Err(format!("No tile {} in the draft pool", pool_idx));
+        }
+        let tile = self.draft_pool.remove(pool_idx);
+        self.alive_players[self.current_player_idx]
+            .tiles_in_hand
+            .push(tile);
+        self.history.draft_picks.push(pool_idx);
+        self.current_player_idx = if self.is_drafting() {
+            let num_players = self.alive_players.len();
+            let picks = self.history.draft_picks.len();
+            let i = picks % num_players;
+            if (picks / num_players).is_multiple_of(2) {
+                i
+            } else {
+                num_players - 1 - i
+            }
+        } else {
+            0
+        };
+        Ok(())
+    }
+    pub fn rules(&self) -> &Rules {
+        &self.history.rules
+    }
+    pub fn team(&self, board_index: usize) -> Option<usize> {
+        self.history.rules.teams.get(board_index).copied()
+    }
+    // Players on the same side win or lose together: a team, or just one
+    // player when there are no teams.
+    fn side(&self, board_index: usize) -> usize {
+        self.team(board_index).unwrap_or(board_index)
+    }
+    // Whether too few players are left for the game to go on.
+    fn too_few_left(&self) -> bool {
+        match self.history.rules.scoring {
+            Scoring::LastStanding => self.num_sides_alive() <= 1,
+            Scoring::LongestPath { .. } => self.alive_players.is_empty(),
+        }
+    }
+    // Points under longest-path scoring: the segments a player's trail
+    // crossed, plus the bonus if they're still alive.
+    pub fn path_points(&self, board_index: usize) -> usize {
+        let trail = &self.board.players[board_index];
+        let bonus = match self.history.rules.scoring {
+            Scoring::LongestPath { survival_bonus }
+                if trail.last().unwrap().alive =>
+            {
+                survival_bonus
+            }
+            _ => 0,
+        };
+        trail.len() - 1 + bonus
+    }
+    // Longest-path points for a whole side.
+    fn side_points(&self, board_index: usize) -> usize {
+        let side = self.side(board_index);
+        (0..self.board.players.len())
+            .filter(|&bidx| self.side(bidx) == side)
+            .map(|bidx| self.path_points(bidx))
+            .sum()
+    }
+    fn num_sides_alive(&self) -> usize {
+        let mut sides: Vec<usize> = self
+            .alive_players
+            .iter()
+            .map(|p| self.side(p.board_index))
+            .collect();
+        sides.sort_unstable();
+        sides.dedup();
+        sides.len()
+    }
+    // Whether the rules let one player see another's hand.
+    fn can_see_hand(&self, viewer: usize, other: usize) -> bool {
+        let rules = &self.history.rules;
+        rules.open_hands
+            || (rules.open_team_hands
+                && self.team(viewer).is_some()
+                && self.team(viewer) == self.team(other))
+    }
+    // The other living players whose hands the given player can see.
+    pub fn visible_hands(&self, username: &str) -> Vec<&Player> {
+        let Some(me) = self.get_player(username) else {
+            return Vec::new();
+        };
+        self.alive_players
+            .iter()
+            .filter(|p| p.username != username)
+            .filter(|p| self.can_see_hand(me.board_index, p.board_index))
+            .collect()
+    }
+    // The tiles left in the stack, next draw first, if the stack is public.
+    pub fn upcoming_tiles(&self) -> Option<Vec<Tile>> {
+        self.history
+            .rules
+            .public_stack
+            .then(|| self.tile_stack.iter().rev().copied().collect())
+    }
+    // Changes how far apart players must start, before anyone registers.
+    pub fn set_min_start_spacing(
+        &mut self,
+        spacing: EdgePos,
+    ) -> Result<(), String> {
+        if !self.history.players.is_empty() {
+            return Err("Players have already registered".to_owned());
+        }
+        if !(0..=MAX_START_SPACING).contains(&spacing) {
+            return Err(format!("Invalid start spacing: {}", spacing));
+        }
+        self.history.min_start_spacing = spacing;
+        Ok(())
     }
     pub fn register_player(
         &mut self,
         username: String,
         start_position: Position,
     ) -> Result<(), String> {
+        if self.rules_set {
+            return Err("Players can't join once the rules are set".to_owned());
+        }
         let pos = cmp::max(0, self.tile_stack.len() as i32 - TILES_PER_PLAYER)
             as usize;
-        self.alive_players.push(Player {
-            username,
-            board_index: self.board.add_player(start_position)?,
-            tiles_in_hand: self.tile_stack.split_off(pos),
-        });
+        let board_index = self.board.add_player(
+            start_position.clone(),
+            self.history.min_start_spacing,
+        )?;
+        self.eliminated_on.push(None);
+        self.history
+            .players
+            .push((username.clone(), start_position));
+        // Keep players in turn order, behind anyone sharing their seat.
+        let order = self.seat_order(board_index);
+        let idx = self
+            .alive_players
+            .iter()
+            .position(|p| self.seat_order(p.board_index) > order)
+            .unwrap_or(self.alive_players.len());
+        self.alive_players.insert(
+            idx,
+            Player {
+                username,
+                board_index,
+                tiles_in_hand: self.tile_stack.split_off(pos),
+            },
+        );
         Ok(())
     }
+    // How many seats on a player's starting seat is from the first registered
+    // player's. The first player moves first and turns go clockwise around
+    // the edge from there, as does the dragon; interior seats come after the
+    // edge seats, in reading order.
+    fn seat_order(&self, board_index: usize) -> usize {
+        let seat =
+            |bidx: usize| seat_index(&self.board.players[bidx][0]).unwrap_or(0);
+        (seat(board_index) + NUM_SEATS - seat(0)) % NUM_SEATS
+    }
+    // The index of the first alive player clockwise after the given seat.
+    fn next_player_after(&self, order: usize) -> usize {
+        self.alive_players
+            .iter()
+            .position(|p| self.seat_order(p.board_index) > order)
+            .unwrap_or(0)
+    }
+    // Reshuffles the tiles nobody has drawn yet.
+    pub(crate) fn shuffle_stack(&mut self, rng: &mut impl rand::Rng) {
+        self.tile_stack.shuffle(rng);
+    }
     pub fn take_turn(
         &mut self,
         tile_index: usize,
         facing: Direction,
-    ) -> Option<Vec<String>> {
+    ) -> Result<Option<GameResult>, String> {
+        self.take_turn_with_spin(tile_index, facing, None)
+    }
+    // Takes a turn, and if the tile is a spinner, turns the tile on its
+    // `spin` side.
+    pub fn take_turn_with_spin(
+        &mut self,
+        tile_index: usize,
+        facing: Direction,
+        spin: Option<Direction>,
+    ) -> Result<Option<GameResult>, String> {
+        if self.is_over() {
+            return Err("Game is over".to_owned());
+        }
+        // Nobody plays until the draft is done.
+        if self.is_drafting() {
+            return Err("Tiles are still being drafted".to_owned());
+        }
+        if tile_index >= self.current_player().tiles_in_hand.len() {
+            return Err(format!("No tile {} in hand", tile_index));
+        }
         let bidx = self.alive_players[self.current_player_idx].board_index;
+        if let Some(dir) = spin {
+            let tile = self.current_player().tiles_in_hand.get(tile_index);
+            if tile.is_none_or(|t| t.effect != Some(Effect::Spinner)) {
+                return Err("Only a spinner can turn another tile".to_owned());
+            }
+            let (row, col) = self.current_player_pos().next_tile_coords();
+            if !self.board.can_spin(row, col, dir) {
+                return Err(format!("No tile to turn to the {:?}", dir));
+            }
+        }
+        let order = self.seat_order(bidx);
+        self.history.moves.push(Move {
+            board_index: bidx,
+            tile_idx: tile_index,
+            facing,
+            spin,
+        });
         {
             let p = &mut self.alive_players[self.current_player_idx];
-            if tile_index < p.tiles_in_hand.len() {
-                let tile = p.tiles_in_hand.remove(tile_index);
-                self.board.play_tile(bidx, &tile, facing);
-                // Replace the played tile, if possible.
-                if let Some(new_tile) = self.tile_stack.pop() {
-                    p.tiles_in_hand.push(new_tile);
-                } else if self.dragon_player_bidx.is_none() {
-                    self.dragon_player_bidx = Some(bidx);
-                }
+            let tile = p.tiles_in_hand.remove(tile_index);
+            self.board.play_tile_with_spin(bidx, &tile, facing, spin);
+            // Replace the played tile, if possible.
+            if let Some(new_tile) = self.tile_stack.pop() {
+                p.tiles_in_hand.push(new_tile);
+            } else if self.dragon_player_bidx.is_none() {
+                self.dragon_player_bidx = Some(bidx);
             }
         }
+        self.record_eliminations();
+        // Puzzles end after the solver's move.
+        if let Some(puzzle) = &self.history.puzzle {
+            let mut rest = puzzle.players[0].hand.clone();
+            rest.remove(tile_index);
+            let solved = puzzle.objective.is_met(
+                &puzzle.board,
+                &self.board,
+                bidx,
+                &rest,
+            );
+            self.puzzle_solved = Some(solved);
+            let solver = &self.alive_players[self.current_player_idx];
+            self.result = Some(if solved {
+                GameResult::Winner {
+                    player: solver.username.clone(),
+                }
+            } else {
+                GameResult::NoWinner
+            });
+            return Ok(self.result.clone());
+        }
+        Ok(self.end_turn(order))
+    }
+    // Applies the recorded forfeits that came after `num_played` moves.
+    pub(crate) fn replay_forfeits(
+        &mut self,
+        forfeits: &[(usize, usize)],
+        num_played: usize,
+    ) -> Result<(), String> {
+        for &(_, bidx) in forfeits.iter().filter(|f| f.0 == num_played) {
+            let player = self
+                .alive_players
+                .iter()
+                .find(|p| p.board_index == bidx)
+                .ok_or(format!("Player {} can't resign", bidx))?;
+            self.resign(&player.username.clone())?;
+        }
+        Ok(())
+    }
+    // Knocks the current player out without a move, e.g. when their time
+    // runs out.
+    pub fn forfeit_turn(&mut self) -> Result<Option<GameResult>, String> {
+        if self.is_over() {
+            return Err("Game is over".to_owned());
+        }
+        self.resign(&self.current_player().username.clone())
+    }
+    // Takes a player out of the game at their request, on their turn or not.
+    // Their tiles go back to the stack as if they'd died.
+    pub fn resign(
+        &mut self,
+        username: &str,
+    ) -> Result<Option<GameResult>, String> {
+        if self.is_over() {
+            return Err("Game is over".to_owned());
+        }
+        if self.is_drafting() {
+            return Err("Can't resign during the draft".to_owned());
+        }
+        let bidx = self
+            .alive_players
+            .iter()
+            .find(|p| p.username == username)
+            .ok_or("Not playing in this game")?
+            .board_index;
+        let current = self.current_player().board_index;
+        let num_moves = self.history.moves.len();
+        self.history.forfeits.push((num_moves, bidx));
+        self.board.players[bidx].last_mut().unwrap().alive = false;
+        self.record_eliminations();
+        if bidx == current {
+            return Ok(self.end_turn(self.seat_order(bidx)));
+        }
+        // Someone else is mid-turn, so it stays theirs.
+        self.remove_dead_players();
+        if self.too_few_left() {
+            return Ok(self.finish());
+        }
+        self.distribute_tiles();
+        self.current_player_idx = self
+            .alive_players
+            .iter()
+            .position(|p| p.board_index == current)
+            .unwrap();
+        Ok(if self.is_over() { self.finish() } else { None })
+    }
+    // Clears out anyone who died, then passes play clockwise from the seat
+    // whose turn just ended.
+    fn end_turn(&mut self, order: usize) -> Option<GameResult> {
         // Check for any newly-dead players.
         if self.remove_dead_players() {
             // Check for game over.
-            if self.alive_players.len() <= 1 {
-                return Some(
-                    self.alive_players
-                        .iter()
-                        .map(|p| p.username.clone())
-                        .collect(),
-                );
+            if self.too_few_left() {
+                return self.finish();
             }
             // Distribute tiles starting from the dragon player.
             self.distribute_tiles();
-            // Update the current player index.
-            if let Some(idx) =
-                self.alive_players.iter().position(|p| p.board_index > bidx)
-            {
-                self.current_player_idx = idx;
-            } else {
-                self.current_player_idx = 0;
-            }
-        } else {
-            // Move to the next alive player.
-            self.current_player_idx += 1;
-            self.current_player_idx %= self.alive_players.len();
         }
+        // Move to the next alive player clockwise, even if this one died.
+        self.current_player_idx = self.next_player_after(order);
         if self.is_over() {
-            // All remaining players win!
-            Some(
-                self.alive_players
-                    .iter()
-                    .map(|p| p.username.clone())
-                    .collect(),
-            )
+            self.finish()
         } else {
             // Game is still going.
             None
         }
     }
+    // Ends the game. All remaining players win, along with their teammates!
+    // Or with longest-path scoring, whoever has the most points.
+    fn finish(&mut self) -> Option<GameResult> {
+        let players: Vec<(usize, &String)> = self
+            .registered_board_indices()
+            .into_iter()
+            .zip(self.history.players.iter().map(|(name, _)| name))
+            .collect();
+        let winners =
+            if let Scoring::LongestPath { .. } = self.history.rules.scoring {
+                let best = players
+                    .iter()
+                    .map(|&(bidx, _)| self.side_points(bidx))
+                    .max();
+                players
+                    .iter()
+                    .filter(|&&(bidx, _)| Some(self.side_points(bidx)) == best)
+                    .map(|&(_, username)| username.clone())
+                    .collect()
+            } else if self.history.rules.is_team_game() {
+                let sides: Vec<usize> = self
+                    .alive_players
+                    .iter()
+                    .map(|p| self.side(p.board_index))
+                    .collect();
+                players
+                    .iter()
+                    .filter(|&&(bidx, _)| sides.contains(&self.side(bidx)))
+                    .map(|&(_, username)| username.clone())
+                    .collect()
+            } else {
+                self.alive_players
+                    .iter()
+                    .map(|p| p.username.clone())
+                    .collect()
+            };
+        self.result = Some(GameResult::from_winners(winners));
+        self.result.clone()
+    }
+    // Notes the current move as the one that knocked out anyone newly dead.
+    fn record_eliminations(&mut self) {
+        let num_moves = self.history.moves.len();
+        self.eliminated_on.resize(self.board.players.len(), None);
+        for (trail, out) in
+            self.board.players.iter().zip(&mut self.eliminated_on)
+        {
+            if !trail.last().unwrap().alive && out.is_none() {
+                *out = Some(num_moves);
+            }
+        }
+    }
     fn remove_dead_players(&mut self) -> bool {
         let mut newly_dead = false;
+        let mut dragon_died = false;
         let mut idx = 0;
         while idx < self.alive_players.len() {
             let bidx = self.alive_players[idx].board_index;
@@ -118,24 +817,23 @@ impl GameManager {
                 let mut dead = self.alive_players.remove(idx);
                 // Return tiles to the stack.
                 self.tile_stack.append(&mut dead.tiles_in_hand);
-                // If they were the dragon player, give the dragon to the next
-                // player missing tiles.
-                if self.dragon_player_bidx == Some(bidx) {
-                    // Technically this should go in order starting from the
-                    // current player, but this is easier for now.
-                    self.dragon_player_bidx = self
-                        .alive_players
-                        .iter()
-                        .find(|p| {
-                            p.tiles_in_hand.len() < TILES_PER_PLAYER as usize
-                        })
-                        .map(|p| p.board_index);
-                }
+                dragon_died |= self.dragon_player_bidx == Some(bidx);
                 info!("Player died: {}", dead.username);
             } else {
                 idx += 1;
             }
         }
+        // If the dragon player died, the dragon goes to the next surviving
+        // player clockwise from them who is missing tiles.
+        if dragon_died && !self.alive_players.is_empty() {
+            let order = self.seat_order(self.dragon_player_bidx.unwrap());
+            let start = self.next_player_after(order);
+            let n = self.alive_players.len();
+            self.dragon_player_bidx = (0..n)
+                .map(|i| &self.alive_players[(start + i) % n])
+                .find(|p| p.tiles_in_hand.len() < TILES_PER_PLAYER as usize)
+                .map(|p| p.board_index);
+        }
         newly_dead
     }
     fn distribute_tiles(&mut self) {
@@ -186,28 +884,826 @@ impl GameManager {
             .last()
             .unwrap()
     }
+    // Number of tiles left to draw.
+    pub fn tiles_remaining(&self) -> usize {
+        self.tile_stack.len()
+    }
+    pub fn current_observation(&self) -> PlayerObservation {
+        self.observation(self.current_player_idx)
+    }
+    fn observation(&self, player_idx: usize) -> PlayerObservation {
+        let me = &self.alive_players[player_idx];
+        let num_players = self.alive_players.len();
+        let opponents = (1..num_players)
+            .map(|i| &self.alive_players[(player_idx + i) % num_players])
+            .map(|p| OpponentInfo {
+                board_index: p.board_index,
+                hand_size: p.tiles_in_hand.len(),
+                hand: self
+                    .can_see_hand(me.board_index, p.board_index)
+                    .then(|| p.tiles_in_hand.clone()),
+            })
+            .collect();
+        PlayerObservation {
+            board: self.board.clone(),
+            board_index: me.board_index,
+            hand: me.tiles_in_hand.clone(),
+            tiles_remaining: self.tiles_remaining(),
+            opponents,
+            dragon_holder: self.dragon_player_bidx,
+            upcoming_tiles: self.upcoming_tiles(),
+            draft_pool: self.draft_pool.clone(),
+        }
+    }
+    // Asks an agent of the given strength what the player should play next,
+    // using only what that player can see.
+    pub fn suggest_move(
+        &self,
+        player_name: &str,
+        strength: usize,
+    ) -> Option<Suggestion> {
+        let player_idx = self
+            .alive_players
+            .iter()
+            .position(|p| p.username == player_name)?;
+        let obs = self.observation(player_idx);
+        if obs.hand.is_empty() || self.is_drafting() {
+            return None;
+        }
+        let deadline = Instant::now() + HINT_TIME_BUDGET;
+        let (tile_idx, facing) =
+            create_agent(strength).choose_action(&obs, deadline);
+        // Play it out on a scratch board to explain the outcome.
+        let mut board = obs.board.clone();
+        board.play_tile(obs.board_index, &obs.hand[tile_idx], facing);
+        let end_pos = board.players[obs.board_index].last().unwrap();
+        let mut explanation = if end_pos.alive {
+            let next = end_pos.next_tile_position();
+            let dist = [next.row, 5 - next.row, next.col, 5 - next.col]
+                .into_iter()
+                .min()
+                .unwrap();
+            format!("survives, ends {} cells from edge", dist)
+        } else {
+            // The chosen move can die even when another one wouldn't.
+            let any_survives = obs.hand.iter().any(|tile| {
+                Direction::all().any(|dir| {
+                    let mut board = obs.board.clone();
+                    board.play_tile(obs.board_index, tile, dir);
+                    board.players[obs.board_index].last().unwrap().alive
+                })
+            });
+            if any_survives {
+                "doesn't survive".to_owned()
+            } else {
+                "no move survives".to_owned()
+            }
+        };
+        let num_eliminated = obs
+            .opponents
+            .iter()
+            .filter(|o| !board.players[o.board_index].last().unwrap().alive)
+            .count();
+        if num_eliminated > 0 {
+            explanation +=
+                &format!(", eliminates {} opponent(s)", num_eliminated);
+        }
+        Some(Suggestion {
+            tile_idx,
+            facing,
+            explanation,
+        })
+    }
     pub fn get_player(&self, player_name: &str) -> Option<&Player> {
         self.alive_players
             .iter()
             .find(|&p| p.username == player_name)
     }
-    pub fn player_scores(&self) -> Vec<i32> {
-        self.board
-            .players
-            .iter()
-            .map(|trail| {
-                let n = trail.len() as i32 - 1;
-                if trail.last().unwrap().alive {
-                    n + 1000
+    // Board indices of the players, in the order they registered. Puzzles
+    // can start with the solver anywhere on the board.
+    fn registered_board_indices(&self) -> Vec<usize> {
+        match &self.history.puzzle {
+            Some(puzzle) => {
+                puzzle.players.iter().map(|p| p.board_index).collect()
+            }
+            None => (0..self.history.players.len()).collect(),
+        }
+    }
+    // Where each player stands, in the order they registered.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self
+            .registered_board_indices()
+            .into_iter()
+            .zip(self.history.players.iter())
+            .map(|(board_index, (username, _))| {
+                let trail = &self.board.players[board_index];
+                // A restored board comes without a record of who died when.
+                let eliminated_on = if trail.last().unwrap().alive {
+                    None
                 } else {
-                    n - 1
+                    Some(self.eliminated_on[board_index].unwrap_or(0))
+                };
+                Standing {
+                    username: username.clone(),
+                    board_index,
+                    placement: 0,
+                    eliminated_on,
+                    path_length: trail.len() - 1,
                 }
             })
+            .collect();
+        // Later eliminations rank higher, and survivors highest of all.
+        // Teammates rank as high as whoever lasted longest among them. With
+        // longest-path scoring, it's points that count instead.
+        let lasted: Vec<usize> = standings
+            .iter()
+            .map(|s| {
+                if let Scoring::LongestPath { .. } = self.history.rules.scoring
+                {
+                    return self.side_points(s.board_index);
+                }
+                let side = self.side(s.board_index);
+                standings
+                    .iter()
+                    .filter(|t| self.side(t.board_index) == side)
+                    .map(|t| t.eliminated_on.unwrap_or(usize::MAX))
+                    .max()
+                    .unwrap()
+            })
+            .collect();
+        for (s, &mine) in standings.iter_mut().zip(&lasted) {
+            s.placement = 1 + lasted.iter().filter(|&&l| l > mine).count();
+        }
+        standings
+    }
+    // Scores for each player, in the order they registered.
+    pub fn scores(&self, weights: &ScoreWeights) -> Vec<i32> {
+        let standings = self.standings();
+        standings
+            .iter()
+            .map(|s| {
+                let outlasted = standings
+                    .iter()
+                    .filter(|other| other.placement > s.placement)
+                    .count();
+                weights.score(s, outlasted)
+            })
             .collect()
     }
+    pub fn player_scores(&self) -> Vec<i32> {
+        self.scores(&ScoreWeights::default())
+    }
     pub fn is_over(&self) -> bool {
-        self.alive_players.len() <= 1
+        self.puzzle_solved.is_some()
+            || self.too_few_left()
             || (self.tile_stack.is_empty()
+                && !self.is_drafting()
                 && self.current_player().tiles_in_hand.is_empty())
     }
 }
+
+#[test]
+fn test_observation_hides_other_hands() {
+    let mut game = GameManager::new(&mut rand::rng());
+    game.register_player("foo".into(), crate::board::edge_position(0))
+        .unwrap();
+    game.register_player("bar".into(), crate::board::edge_position(20))
+        .unwrap();
+    game.register_player("baz".into(), crate::board::edge_position(40))
+        .unwrap();
+    let obs = game.current_observation();
+    assert_eq!(obs.board_index, 0);
+    assert_eq!(obs.hand.len(), 3);
+    assert_eq!(obs.tiles_remaining, 35 - 9);
+    assert_eq!(obs.dragon_holder, None);
+    let order: Vec<usize> =
+        obs.opponents.iter().map(|o| o.board_index).collect();
+    assert_eq!(order, vec![1, 2]);
+    assert!(obs.opponents.iter().all(|o| o.hand_size == 3));
+    // The board is still empty, so the only tiles in the serialized
+    // observation should be this player's own hand.
+    let json = serde_json::to_string(&obs).unwrap();
+    let hidden = game.alive_players[1..]
+        .iter()
+        .flat_map(|p| &p.tiles_in_hand)
+        .chain(&game.tile_stack);
+    for tile in hidden {
+        let tile = serde_json::to_string(tile).unwrap();
+        assert!(!json.contains(&tile), "{} is visible", tile);
+    }
+    for tile in &game.alive_players[0].tiles_in_hand {
+        assert!(json.contains(&serde_json::to_string(tile).unwrap()));
+    }
+    assert!(!json.contains("upcoming_tiles"));
+}
+
+#[test]
+fn test_open_hands_observation() {
+    let mut game = seated_game(&[0, 20, 40]);
+    game.set_rules(Rules {
+        open_hands: true,
+        public_stack: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let obs = game.current_observation();
+    for o in &obs.opponents {
+        let player = game
+            .alive_players
+            .iter()
+            .find(|p| p.board_index == o.board_index)
+            .unwrap();
+        assert_eq!(o.hand.as_ref(), Some(&player.tiles_in_hand));
+    }
+    let upcoming = obs.upcoming_tiles.unwrap();
+    assert_eq!(upcoming.len(), obs.tiles_remaining);
+    assert_eq!(upcoming.first(), game.tile_stack.last());
+    assert_eq!(game.visible_hands("p0").len(), 2);
+}
+
+#[test]
+fn test_draft() {
+    let mut game = seated_game(&[0, 16, 32]);
+    game.set_rules(Rules {
+        draft: true,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(game.draft_pool().len(), 9);
+    assert!(
+        game.alive_players
+            .iter()
+            .all(|p| p.tiles_in_hand.is_empty())
+    );
+    assert_eq!(game.current_observation().draft_pool.len(), 9);
+    assert!(game.suggest_move("p0", 0).is_none());
+    assert!(game.take_turn_with_spin(0, Direction::North, None).is_err());
+    let seat = crate::board::edge_position(8);
+    assert!(game.register_player("p3".into(), seat).is_err());
+    let mut drafters = Vec::new();
+    while game.is_drafting() {
+        drafters.push(game.current_player().username.clone());
+        game.draft_tile(game.draft_pool().len() - 1).unwrap();
+    }
+    let snake = ["p0", "p1", "p2", "p2", "p1", "p0", "p0", "p1", "p2"];
+    assert_eq!(drafters, snake);
+    assert_eq!(game.current_player().username, "p0");
+    assert!(
+        game.alive_players
+            .iter()
+            .all(|p| p.tiles_in_hand.len() == 3)
+    );
+    assert!(game.draft_tile(0).is_err());
+    assert!(game.set_rules(Rules::default()).is_err());
+
+    let replayed = GameManager::replay(game.history(), 0).unwrap();
+    for (a, b) in replayed.alive_players.iter().zip(&game.alive_players) {
+        assert_eq!(a.tiles_in_hand, b.tiles_in_hand);
+    }
+}
+
+#[test]
+fn test_power_tiles_in_stack() {
+    let mut game = seated_game(&[0, 16]);
+    let rules = Rules {
+        power_tiles: true,
+        ..Default::default()
+    };
+    game.set_rules(rules.clone()).unwrap();
+    game.set_rules(rules).unwrap();
+    let powers = |game: &GameManager| {
+        game.tile_stack
+            .iter()
+            .filter(|t| t.effect.is_some())
+            .count()
+    };
+    assert_eq!(powers(&game), power_tiles().len());
+    assert_eq!(game.tiles_remaining(), 35 - 6 + power_tiles().len());
+    // Nobody starts with one.
+    assert!(
+        game.alive_players
+            .iter()
+            .flat_map(|p| p.tiles_in_hand.iter())
+            .all(|t| t.effect.is_none())
+    );
+    let replayed = GameManager::replay(game.history(), 0).unwrap();
+    assert_eq!(replayed.tile_stack, game.tile_stack);
+    game.set_rules(Rules::default()).unwrap();
+    assert_eq!(powers(&game), 0);
+
+    // A supplied stack is dealt exactly as given, whatever the rules.
+    let mut stack = all_tiles();
+    stack.insert(10, power_tiles()[0]);
+    let mut game = GameManager::with_tile_stack(stack.clone());
+    for (i, seat) in [0, 16].into_iter().enumerate() {
+        let pos = crate::board::edge_position(seat);
+        game.register_player(format!("p{}", i), pos).unwrap();
+    }
+    let mut rules = Rules::default();
+    game.set_rules(rules.clone()).unwrap();
+    assert_eq!(powers(&game), 1);
+    rules.power_tiles = true;
+    game.set_rules(rules).unwrap();
+    assert_eq!(powers(&game), 1);
+    let replayed = GameManager::replay(game.history(), 0).unwrap();
+    assert_eq!(replayed.tile_stack, game.tile_stack);
+}
+
+#[test]
+fn test_spin() {
+    use crate::agent::{Agent, AvoidSuddenDeathAgent};
+    let mut stack = all_tiles();
+    stack.push(power_tiles()[0]);
+    stack.push(all_tiles()[5]);
+    let mut game = GameManager::with_tile_stack(stack);
+    for (i, seat) in [0, 24].into_iter().enumerate() {
+        let pos = crate::board::edge_position(seat);
+        game.register_player(format!("p{}", i), pos).unwrap();
+    }
+    // p0 goes down through (0, 0) and waits above (1, 0).
+    game.take_turn(2, Direction::North).unwrap();
+    let obs = game.current_observation();
+    let (idx, facing) =
+        AvoidSuddenDeathAgent.choose_action(&obs, Instant::now());
+    game.take_turn(idx, facing).unwrap();
+
+    let north = Some(Direction::North);
+    assert!(
+        game.take_turn_with_spin(0, Direction::North, north)
+            .is_err()
+    );
+    let south = Some(Direction::South);
+    assert!(
+        game.take_turn_with_spin(1, Direction::North, south)
+            .is_err()
+    );
+    assert_eq!(game.history().moves.len(), 2);
+    // Turning the tile p0 came through leaves the way they went alone.
+    let before = game.board.players[0].clone();
+    game.take_turn_with_spin(1, Direction::North, north)
+        .unwrap();
+    assert_eq!(game.history().moves[2].spin, north);
+    assert_eq!(game.board.players[0][..before.len()], before[..]);
+    let turned = game.board.get_tile(&before[1]).unwrap().unwrap();
+    assert_eq!(turned.1, Direction::East);
+
+    let replayed = GameManager::replay(game.history(), 3).unwrap();
+    assert_eq!(format!("{:?}", replayed.board), format!("{:?}", game.board));
+}
+
+#[test]
+fn test_start_spacing() {
+    let mut game = seated_game(&[10]);
+    let err =
+        game.register_player("p1".into(), crate::board::edge_position(11));
+    assert!(err.unwrap_err().contains("too close"));
+    assert!(game.set_min_start_spacing(0).is_err());
+
+    let mut game = GameManager::new(&mut rand::rng());
+    assert!(game.set_min_start_spacing(-1).is_err());
+    assert!(game.set_min_start_spacing(25).is_err());
+    game.set_min_start_spacing(0).unwrap();
+    for name in ["a", "b"] {
+        let pos = crate::board::edge_position(11);
+        game.register_player(name.into(), pos).unwrap();
+    }
+    assert!(GameManager::replay(game.history(), 0).is_ok());
+}
+
+#[test]
+fn test_forfeit_turn() {
+    use crate::agent::{Agent, AvoidSuddenDeathAgent};
+    let mut game = seated_game(&[0, 12, 24]);
+    let obs = game.current_observation();
+    let (idx, facing) =
+        AvoidSuddenDeathAgent.choose_action(&obs, Instant::now());
+    game.take_turn(idx, facing).unwrap();
+    assert_eq!(game.current_player().username, "p1");
+    let stack_size = game.tiles_remaining();
+    assert_eq!(game.forfeit_turn(), Ok(None));
+    assert_eq!(game.tiles_remaining(), stack_size + 3);
+    assert_eq!(turn_order(&game), ["p0", "p2"]);
+    assert_eq!(game.current_player().username, "p2");
+    assert_eq!(game.standings()[1].eliminated_on, Some(1));
+
+    let replayed = GameManager::replay(game.history(), 1).unwrap();
+    assert_eq!(turn_order(&replayed), ["p0", "p2"]);
+    assert_eq!(replayed.tile_stack, game.tile_stack);
+
+    let result = game.forfeit_turn().unwrap().unwrap();
+    assert_eq!(result.winners(), ["p0"]);
+    assert!(game.forfeit_turn().is_err());
+}
+
+#[test]
+fn test_resign() {
+    use crate::agent::{Agent, AvoidSuddenDeathAgent};
+    let mut game = seated_game(&[0, 16, 32]);
+    let stack_size = game.tiles_remaining();
+    // Resigning out of turn leaves it p0's turn.
+    assert_eq!(game.resign("p1"), Ok(None));
+    assert_eq!(game.tiles_remaining(), stack_size + 3);
+    assert_eq!(turn_order(&game), ["p0", "p2"]);
+    assert_eq!(game.current_player().username, "p0");
+    assert!(game.resign("p1").is_err());
+    assert!(game.resign("nobody").is_err());
+
+    let obs = game.current_observation();
+    let (idx, facing) =
+        AvoidSuddenDeathAgent.choose_action(&obs, Instant::now());
+    game.take_turn(idx, facing).unwrap();
+    assert_eq!(game.current_player().username, "p2");
+    let replayed = GameManager::replay(game.history(), 1).unwrap();
+    assert_eq!(turn_order(&replayed), ["p0", "p2"]);
+    assert_eq!(replayed.current_player().username, "p2");
+
+    let result = game.resign("p0").unwrap().unwrap();
+    assert_eq!(result.winners(), ["p2"]);
+}
+
+#[test]
+fn test_puzzle_scoring() {
+    use crate::agent::{Agent, AvoidSuddenDeathAgent};
+    use crate::puzzle::{Objective, Puzzle, PuzzlePlayer};
+    let mut game = seated_game(&[0, 24]);
+    let obs = game.current_observation();
+    let (idx, facing) =
+        AvoidSuddenDeathAgent.choose_action(&obs, Instant::now());
+    game.take_turn(idx, facing).unwrap();
+    assert!(game.board.players[0].len() > game.board.players[1].len());
+    // The solver is the second player on the board.
+    let players = [1, 0].map(|bidx| PuzzlePlayer {
+        board_index: bidx,
+        hand: game.alive_players[bidx].tiles_in_hand.clone(),
+    });
+    let puzzle = Puzzle {
+        board: game.board.clone(),
+        players: players.to_vec(),
+        tile_stack: game.tile_stack.clone(),
+        dragon_holder: None,
+        objective: Objective::Survive,
+        solution: (0, Direction::North),
+    };
+    let names = vec!["solver".to_owned(), "other".to_owned()];
+    let mut game = GameManager::from_puzzle(&puzzle, names).unwrap();
+    let rules = Rules {
+        scoring: Scoring::LongestPath { survival_bonus: 0 },
+        ..Default::default()
+    };
+    game.set_rules(rules).unwrap();
+    assert_eq!(game.resign("other"), Ok(None));
+    let result = game.resign("solver").unwrap().unwrap();
+    // The other player's trail is longer.
+    assert_eq!(result.winners(), ["other"]);
+    assert_eq!(game.standings()[1].placement, 1);
+}
+
+#[test]
+fn test_dragon_passes_on_resign() {
+    let mut game = seated_game(&[0, 16, 32]);
+    // Nobody can fill their hand, so the dragon waits with p1.
+    game.tile_stack.clear();
+    for player in game.alive_players.iter_mut() {
+        player.tiles_in_hand.pop();
+    }
+    game.dragon_player_bidx = Some(1);
+    game.resign("p1").unwrap();
+    // p1's tiles go to p2 first, clockwise from the dragon.
+    assert_eq!(game.alive_players[1].tiles_in_hand.len(), 3);
+    assert_eq!(game.alive_players[0].tiles_in_hand.len(), 3);
+    assert_eq!(game.dragon_player_bidx, None);
+    assert!(game.tile_stack.is_empty());
+}
+
+#[test]
+fn test_replay_history() {
+    use crate::agent::Agent;
+    use crate::agent::AvoidSuddenDeathAgent;
+    let mut game = GameManager::new(&mut rand::rng());
+    game.register_player("foo".into(), crate::board::edge_position(3))
+        .unwrap();
+    game.register_player("bar".into(), crate::board::edge_position(27))
+        .unwrap();
+    let agent = AvoidSuddenDeathAgent;
+    while !game.is_over() {
+        let (idx, facing) =
+            agent.choose_action(&game.current_observation(), Instant::now());
+        game.take_turn(idx, facing).unwrap();
+    }
+    // Round-trip through JSON, as the web server stores it.
+    let json = serde_json::to_string(game.history()).unwrap();
+    let history: GameHistory = serde_json::from_str(&json).unwrap();
+    let replayed = GameManager::replay(&history, history.moves.len()).unwrap();
+    assert_eq!(replayed.player_scores(), game.player_scores());
+    assert_eq!(replayed.result(), game.result());
+    assert!(replayed.is_over());
+    let start = GameManager::replay(&history, 0).unwrap();
+    assert!(start.board.players.iter().all(|trail| trail.len() == 1));
+}
+
+#[test]
+fn test_suggest_move() {
+    let mut game = GameManager::new(&mut rand::rng());
+    game.register_player("foo".into(), crate::board::edge_position(0))
+        .unwrap();
+    game.register_player("bar".into(), crate::board::edge_position(20))
+        .unwrap();
+    // Hints are available for any alive player, not just the current one.
+    let hint = game.suggest_move("bar", 2).unwrap();
+    assert!(hint.tile_idx < 3);
+    assert!(!hint.explanation.is_empty());
+    assert!(game.suggest_move("nobody", 2).is_none());
+}
+
+#[test]
+fn test_suggest_move_without_escape() {
+    use crate::tiles::Port;
+    // Every path turns back to the side it came in on.
+    let u_turns = all_tiles()
+        .into_iter()
+        .find(|t| {
+            [(Port::A, Port::B), (Port::C, Port::D), (Port::E, Port::F)]
+                .into_iter()
+                .all(|(a, b)| t.traverse(a, Direction::North) == b)
+        })
+        .unwrap();
+    let mut game = seated_game(&[0, 24]);
+    game.alive_players[0].tiles_in_hand = vec![u_turns];
+    let hint = game.suggest_move("p0", 0).unwrap();
+    assert_eq!(hint.explanation, "no move survives");
+}
+
+#[cfg(test)]
+fn seated_game(seats: &[crate::board::EdgePos]) -> GameManager {
+    let mut game = GameManager::new(&mut rand::rng());
+    for (i, &seat) in seats.iter().enumerate() {
+        let pos = crate::board::edge_position(seat);
+        game.register_player(format!("p{}", i), pos).unwrap();
+    }
+    game
+}
+
+#[cfg(test)]
+fn turn_order(game: &GameManager) -> Vec<&str> {
+    game.alive_players
+        .iter()
+        .map(|p| p.username.as_str())
+        .collect()
+}
+
+#[test]
+fn test_turn_order_follows_seats() {
+    // Registered out of seat order: clockwise from p0 it's p3, p1, p2.
+    let mut game = seated_game(&[30, 5, 20, 40]);
+    assert_eq!(turn_order(&game), ["p0", "p3", "p1", "p2"]);
+    assert_eq!(game.current_player().username, "p0");
+    let obs = game.current_observation();
+    let opponents: Vec<usize> =
+        obs.opponents.iter().map(|o| o.board_index).collect();
+    assert_eq!(opponents, [3, 1, 2]);
+
+    // p0 moves and p3, next in line, dies: p1 plays next.
+    let order = game.seat_order(0);
+    game.board.players[3].last_mut().unwrap().alive = false;
+    assert!(game.remove_dead_players());
+    assert_eq!(
+        game.alive_players[game.next_player_after(order)].username,
+        "p1"
+    );
+    // p1 moves and dies along with p2: it's back to p0.
+    let order = game.seat_order(1);
+    game.board.players[1].last_mut().unwrap().alive = false;
+    game.board.players[2].last_mut().unwrap().alive = false;
+    assert!(game.remove_dead_players());
+    assert_eq!(
+        game.alive_players[game.next_player_after(order)].username,
+        "p0"
+    );
+}
+
+#[test]
+fn test_dragon_passes_clockwise() {
+    // Turn order p0, p3, p1, p2, as above.
+    let mut game = seated_game(&[30, 5, 20, 40]);
+    for bidx in [0, 1, 2] {
+        game.alive_players
+            .iter_mut()
+            .find(|p| p.board_index == bidx)
+            .unwrap()
+            .tiles_in_hand
+            .pop();
+    }
+    // p3 holds the dragon and dies: the next player short of tiles
+    // clockwise from them is p1, not p0.
+    game.dragon_player_bidx = Some(3);
+    game.board.players[3].last_mut().unwrap().alive = false;
+    game.remove_dead_players();
+    assert_eq!(game.dragon_player_bidx, Some(1));
+    // The dragon holder gets the first tile, then play continues clockwise.
+    game.tile_stack.truncate(2);
+    game.distribute_tiles();
+    let hand_sizes: Vec<usize> = game
+        .alive_players
+        .iter()
+        .map(|p| p.tiles_in_hand.len())
+        .collect();
+    assert_eq!(turn_order(&game), ["p0", "p1", "p2"]);
+    assert_eq!(hand_sizes, [2, 3, 3]);
+    assert_eq!(game.dragon_player_bidx, Some(0));
+}
+
+#[test]
+fn test_game_result() {
+    let names = |n: &[&str]| n.iter().map(|&s| s.to_owned()).collect();
+    let result = GameResult::from_winners(names(&["a", "b"]));
+    assert!(result.is_winner("b"));
+    assert!(!result.is_winner("c"));
+    assert_eq!(
+        serde_json::to_string(&result).unwrap(),
+        r#"{"outcome":"SharedWin","players":["a","b"]}"#
+    );
+    let result = GameResult::from_winners(names(&["a"]));
+    assert_eq!(result.winners(), ["a"]);
+    assert_eq!(
+        serde_json::to_string(&result).unwrap(),
+        r#"{"outcome":"Winner","player":"a"}"#
+    );
+    assert_eq!(GameResult::from_winners(Vec::new()), GameResult::NoWinner);
+}
+
+#[test]
+fn test_standings() {
+    use crate::agent::{Agent, AvoidSuddenDeathAgent};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    let agent = AvoidSuddenDeathAgent;
+    let weights = ScoreWeights::default();
+    let mut saw_tie = false;
+    for seed in 0..20 {
+        let mut game = GameManager::new(&mut StdRng::seed_from_u64(seed));
+        for (i, pos) in [1, 9, 17, 25, 33, 41].into_iter().enumerate() {
+            let pos = crate::board::edge_position(pos);
+            game.register_player(format!("p{}", i), pos).unwrap();
+        }
+        let mut result = None;
+        while result.is_none() {
+            let obs = game.current_observation();
+            let (idx, facing) = agent.choose_action(&obs, Instant::now());
+            result = game.take_turn(idx, facing).unwrap();
+        }
+        let result = result.unwrap();
+        let standings = game.standings();
+        let scores = game.scores(&weights);
+        assert_eq!(scores, game.player_scores());
+        for (a, score_a) in standings.iter().zip(&scores) {
+            // Winners come first, and everyone knocked out on the same move
+            // shares a place.
+            if result.is_winner(&a.username) {
+                assert_eq!(a.placement, 1);
+            }
+            let better = standings
+                .iter()
+                .filter(|b| {
+                    b.eliminated_on.unwrap_or(usize::MAX)
+                        > a.eliminated_on.unwrap_or(usize::MAX)
+                })
+                .count();
+            assert_eq!(a.placement, better + 1);
+            for (b, score_b) in standings.iter().zip(&scores) {
+                if a.placement < b.placement {
+                    assert!(score_a > score_b);
+                }
+                if a.eliminated_on.is_some() && a.username != b.username {
+                    saw_tie |= a.eliminated_on == b.eliminated_on;
+                }
+            }
+        }
+    }
+    assert!(saw_tie);
+}
+
+#[test]
+fn test_score_weights() {
+    assert!(ScoreWeights::default().validate().is_ok());
+    let win_only = ScoreWeights {
+        per_player_outlasted: 1,
+        per_path: 0,
+    };
+    assert!(win_only.validate().is_ok());
+    let path_heavy = ScoreWeights {
+        per_player_outlasted: 10,
+        per_path: 1,
+    };
+    assert!(path_heavy.validate().is_err());
+    let huge = ScoreWeights {
+        per_player_outlasted: i32::MAX / 2,
+        per_path: 1,
+    };
+    assert!(huge.validate().is_err());
+    let standing = Standing {
+        username: "p0".to_owned(),
+        board_index: 0,
+        placement: 1,
+        eliminated_on: None,
+        path_length: 10,
+    };
+    assert_eq!(huge.score(&standing, 3), i32::MAX);
+}
+
+#[test]
+fn test_team_game() {
+    // Teams alternate in registration order: p0 and p2 against p1 and p3.
+    let mut game = seated_game(&[0, 12, 24, 36]);
+    let mut rules = Rules::with_teams_of(2, 4);
+    rules.open_team_hands = true;
+    game.set_rules(rules).unwrap();
+    assert_eq!(game.team(2), Some(0));
+    let visible: Vec<&str> = game
+        .visible_hands("p1")
+        .iter()
+        .map(|p| p.username.as_str())
+        .collect();
+    assert_eq!(visible, ["p3"]);
+
+    // With one player left on each team, the game goes on.
+    for bidx in [0, 1] {
+        game.board.players[bidx].last_mut().unwrap().alive = false;
+    }
+    game.eliminated_on = vec![Some(1), Some(1), None, None];
+    game.remove_dead_players();
+    assert!(!game.is_over());
+    // Once p3 is out, p2 wins for both of them.
+    game.board.players[3].last_mut().unwrap().alive = false;
+    game.eliminated_on[3] = Some(2);
+    game.remove_dead_players();
+    assert!(game.is_over());
+    let result = game.finish().unwrap();
+    assert_eq!(result.winners(), ["p0", "p2"]);
+    let places: Vec<usize> =
+        game.standings().iter().map(|s| s.placement).collect();
+    assert_eq!(places, [1, 3, 1, 3]);
+
+    // Teams have to cover everyone, and can't be changed mid-game.
+    let mut game = seated_game(&[0, 12, 24]);
+    assert!(game.set_rules(Rules::with_teams_of(2, 4)).is_err());
+    game.take_turn(0, Direction::North).unwrap();
+    assert!(game.set_rules(Rules::default()).is_err());
+}
+
+#[test]
+fn test_longest_path() {
+    use crate::agent::{Agent, AvoidSuddenDeathAgent};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    let agent = AvoidSuddenDeathAgent;
+    let mut played_alone = false;
+    for seed in 0..10 {
+        let mut game = GameManager::new(&mut StdRng::seed_from_u64(seed));
+        for (i, pos) in [2, 14, 26, 38].into_iter().enumerate() {
+            let pos = crate::board::edge_position(pos);
+            game.register_player(format!("p{}", i), pos).unwrap();
+        }
+        let scoring = Scoring::LongestPath { survival_bonus: 5 };
+        game.set_rules(Rules {
+            scoring,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut result = None;
+        while result.is_none() {
+            played_alone |= game.alive_players.len() == 1;
+            let obs = game.current_observation();
+            let (idx, facing) = agent.choose_action(&obs, Instant::now());
+            result = game.take_turn(idx, facing).unwrap();
+        }
+        // The game only ends once the tiles or the players run out.
+        assert!(game.alive_players.is_empty() || game.tiles_remaining() == 0);
+        let points: Vec<usize> = (0..4).map(|b| game.path_points(b)).collect();
+        let best = *points.iter().max().unwrap();
+        let standings = game.standings();
+        for (s, &p) in standings.iter().zip(&points) {
+            let alive = game.board.players[s.board_index].last().unwrap().alive;
+            assert_eq!(p, s.path_length + if alive { 5 } else { 0 });
+            assert_eq!(
+                result.as_ref().unwrap().is_winner(&s.username),
+                p == best
+            );
+            assert_eq!(s.placement == 1, p == best);
+        }
+    }
+    assert!(played_alone);
+}
+
+#[test]
+fn test_rejects_bad_moves() {
+    let mut game = seated_game(&[0, 24]);
+    assert!(game.take_turn(3, Direction::North).is_err());
+    assert!(game.history().moves.is_empty());
+    assert_eq!(game.current_player().username, "p0");
+    assert_eq!(game.current_player().tiles_in_hand.len(), 3);
+
+    game.resign("p0").unwrap();
+    assert!(game.is_over());
+    assert!(game.take_turn(0, Direction::North).is_err());
+    assert!(game.history().moves.is_empty());
+}
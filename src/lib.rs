@@ -1,5 +1,10 @@
 pub mod agent;
+pub mod analysis;
 pub mod api;
 pub mod board;
+pub mod external;
 pub mod game;
+pub mod puzzle;
+pub mod rules;
+pub mod script;
 pub mod tiles;
@@ -0,0 +1,367 @@
+use crate::board::{Board, Position};
+use crate::game::GameManager;
+use crate::tiles::{Direction, Port, Tile};
+use serde::{Deserialize, Serialize};
+
+// What the solver has to achieve with their move.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum Objective {
+    // Stay alive after placing a tile.
+    Survive,
+    // Stay alive and knock out at least this many opponents in one move.
+    Eliminate { opponents: usize },
+    // Stay alive and knock out the player with this board index.
+    EliminateTarget { board_index: usize },
+    // Stay alive and pass through the given cell.
+    ReachCell { row: i8, col: i8 },
+    // Stay alive for this many placements in a row, counting this one and
+    // playing only from the hand. Other players' moves aren't considered.
+    SurviveTurns { turns: usize },
+}
+
+impl Objective {
+    // Checks whether the solver's move from `before` to `after` succeeded.
+    // `rest` is what's left of the solver's hand after the move.
+    pub fn is_met(
+        &self,
+        before: &Board,
+        after: &Board,
+        solver: usize,
+        rest: &[Tile],
+    ) -> bool {
+        let alive = |board: &Board, bidx: usize| {
+            board.players[bidx].last().unwrap().alive
+        };
+        if !alive(after, solver) {
+            return false;
+        }
+        match *self {
+            Objective::Survive => true,
+            Objective::Eliminate { opponents } => {
+                let eliminated = (0..after.players.len())
+                    .filter(|&bidx| bidx != solver)
+                    .filter(|&bidx| alive(before, bidx) && !alive(after, bidx))
+                    .count();
+                eliminated >= opponents
+            }
+            Objective::EliminateTarget { board_index } => {
+                board_index != solver
+                    && alive(before, board_index)
+                    && !alive(after, board_index)
+            }
+            Objective::ReachCell { row, col } => {
+                let start = before.players[solver].len();
+                after.players[solver][start..]
+                    .iter()
+                    .any(|pos| pos.row == row && pos.col == col)
+            }
+            Objective::SurviveTurns { turns } => {
+                survives(after, solver, rest, turns.saturating_sub(1))
+            }
+        }
+    }
+}
+
+// Whether the player can stay alive for `turns` more placements from `hand`.
+// Running out of tiles counts as surviving.
+fn survives(board: &Board, player: usize, hand: &[Tile], turns: usize) -> bool {
+    if !board.players[player].last().unwrap().alive {
+        return false;
+    }
+    if turns == 0 {
+        return true;
+    }
+    hand.is_empty()
+        || hand.iter().enumerate().any(|(i, tile)| {
+            let mut rest = hand.to_vec();
+            rest.remove(i);
+            Direction::all().any(|dir| {
+                let mut next = board.clone();
+                next.play_tile(player, tile, dir);
+                survives(&next, player, &rest, turns - 1)
+            })
+        })
+}
+
+const PORTS: [Port; 8] = [
+    Port::A,
+    Port::B,
+    Port::C,
+    Port::D,
+    Port::E,
+    Port::F,
+    Port::G,
+    Port::H,
+];
+
+// A player still in the game when the puzzle starts.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PuzzlePlayer {
+    pub board_index: usize,
+    pub hand: Vec<Tile>,
+}
+
+// A position taken from a game, with a move for the first player to find.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Puzzle {
+    pub board: Board,
+    // Alive players in turn order, starting with the solver.
+    pub players: Vec<PuzzlePlayer>,
+    // Tiles left to draw, dealt from the end.
+    pub tile_stack: Vec<Tile>,
+    // Board index of the player holding the dragon tile, if any.
+    pub dragon_holder: Option<usize>,
+    pub objective: Objective,
+    // The only move that meets the objective.
+    pub solution: (usize, Direction),
+}
+
+impl Puzzle {
+    // Captures the current player's position in a game, if the given
+    // objective has exactly one solution there.
+    pub fn from_game(game: &GameManager, objective: Objective) -> Option<Self> {
+        let n = game.alive_players.len();
+        let players = (0..n)
+            .map(|i| &game.alive_players[(game.current_player_idx + i) % n])
+            .map(|p| PuzzlePlayer {
+                board_index: p.board_index,
+                hand: p.tiles_in_hand.clone(),
+            })
+            .collect();
+        let mut puzzle = Puzzle {
+            board: game.board.clone(),
+            players,
+            tile_stack: game.tile_stack.clone(),
+            dragon_holder: game.dragon_player_bidx,
+            objective,
+            solution: (0, Direction::North),
+        };
+        let solutions = puzzle.solutions();
+        if solutions.len() != 1 {
+            return None;
+        }
+        puzzle.solution = solutions[0];
+        Some(puzzle)
+    }
+
+    // Every move in the solver's hand that meets the objective. Moves that
+    // leave the board the same, like turning a symmetric tile around, only
+    // count once.
+    pub fn solutions(&self) -> Vec<(usize, Direction)> {
+        let hand_size = self.players[0].hand.len();
+        let mut outcomes = Vec::new();
+        (0..hand_size)
+            .flat_map(|i| Direction::all().map(move |d| (i, d)))
+            .filter(|&(i, d)| self.is_solution(i, d))
+            .filter(|&(i, d)| {
+                let outcome = self.outcome(i, d);
+                let new = !outcomes.contains(&outcome);
+                outcomes.push(outcome);
+                new
+            })
+            .collect()
+    }
+
+    // What a move leaves behind: everyone's trails, and where each port of
+    // the new tile leads.
+    fn outcome(
+        &self,
+        tile_idx: usize,
+        facing: Direction,
+    ) -> (Vec<Vec<Position>>, [Port; 8]) {
+        let me = &self.players[0];
+        let tile = &me.hand[tile_idx];
+        let mut board = self.board.clone();
+        board.play_tile(me.board_index, tile, facing);
+        (board.players, PORTS.map(|p| tile.traverse(p, facing)))
+    }
+
+    pub fn is_solution(&self, tile_idx: usize, facing: Direction) -> bool {
+        let me = &self.players[0];
+        let Some(tile) = me.hand.get(tile_idx) else {
+            return false;
+        };
+        let mut board = self.board.clone();
+        board.play_tile(me.board_index, tile, facing);
+        let mut rest = me.hand.clone();
+        rest.remove(tile_idx);
+        self.objective
+            .is_met(&self.board, &board, me.board_index, &rest)
+    }
+}
+
+#[test]
+fn test_play_puzzle() {
+    use crate::board::edge_position;
+    use crate::game::GameResult;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    // Find an opening with both a winning and a losing move.
+    let puzzle = (0..100)
+        .find_map(|seed| {
+            let mut game = GameManager::new(&mut StdRng::seed_from_u64(seed));
+            game.register_player("p0".into(), edge_position(5)).unwrap();
+            game.register_player("p1".into(), edge_position(30))
+                .unwrap();
+            let mut puzzle = Puzzle {
+                board: game.board.clone(),
+                players: game
+                    .alive_players
+                    .iter()
+                    .map(|p| PuzzlePlayer {
+                        board_index: p.board_index,
+                        hand: p.tiles_in_hand.clone(),
+                    })
+                    .collect(),
+                tile_stack: game.tile_stack.clone(),
+                dragon_holder: None,
+                objective: Objective::Survive,
+                solution: (0, Direction::North),
+            };
+            let solutions = puzzle.solutions();
+            puzzle.solution = *solutions.first()?;
+            (solutions.len() < 12).then_some(puzzle)
+        })
+        .unwrap();
+    let names = vec!["solver".to_owned(), "other".to_owned()];
+    let (idx, facing) = puzzle.solution;
+
+    let mut game = GameManager::from_puzzle(&puzzle, names.clone()).unwrap();
+    assert_eq!(game.current_player().username, "solver");
+    assert!(!game.is_over());
+    let solved = GameResult::Winner {
+        player: "solver".to_owned(),
+    };
+    assert_eq!(game.take_turn(idx, facing), Ok(Some(solved)));
+    assert!(game.is_over());
+    assert_eq!(game.puzzle_solved(), Some(true));
+    // The history replays from the puzzle position.
+    let history = game.history().clone();
+    let replayed = GameManager::replay(&history, 1).unwrap();
+    assert_eq!(replayed.puzzle_solved(), Some(true));
+
+    // A losing move fails the puzzle.
+    let wrong = (0..puzzle.players[0].hand.len())
+        .flat_map(|i| Direction::all().map(move |d| (i, d)))
+        .find(|&(i, d)| !puzzle.is_solution(i, d))
+        .unwrap();
+    let mut game = GameManager::from_puzzle(&puzzle, names).unwrap();
+    let failed = game.take_turn(wrong.0, wrong.1).unwrap();
+    assert_eq!(failed, Some(GameResult::NoWinner));
+    assert_eq!(game.puzzle_solved(), Some(false));
+}
+
+#[test]
+fn test_puzzle_solution_is_unique() {
+    use crate::agent::{Agent, AvoidSuddenDeathAgent};
+    use crate::board::edge_position;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::time::Instant;
+    let agent = AvoidSuddenDeathAgent;
+    let mut found = 0;
+    for seed in 0..20 {
+        let mut game = GameManager::new(&mut StdRng::seed_from_u64(seed));
+        for (i, pos) in [2, 14, 26, 38].into_iter().enumerate() {
+            game.register_player(format!("p{}", i), edge_position(pos))
+                .unwrap();
+        }
+        while !game.is_over() {
+            if let Some(p) = Puzzle::from_game(&game, Objective::Survive) {
+                assert_eq!(p.solutions(), vec![p.solution]);
+                let (idx, facing) = p.solution;
+                assert!(p.is_solution(idx, facing));
+                // Puzzles survive a round trip through JSON.
+                let json = serde_json::to_string(&p).unwrap();
+                let p: Puzzle = serde_json::from_str(&json).unwrap();
+                assert_eq!(p.solutions(), vec![(idx, facing)]);
+                found += 1;
+            }
+            let obs = game.current_observation();
+            let (idx, facing) = agent.choose_action(&obs, Instant::now());
+            game.take_turn(idx, facing).unwrap();
+        }
+    }
+    assert!(found > 0);
+}
+
+#[test]
+fn test_symmetric_moves_count_once() {
+    use crate::board::edge_position;
+    use crate::tiles::all_tiles;
+    // A tile that looks the same whichever way it faces
+    let tile = all_tiles()
+        .into_iter()
+        .find(|t| {
+            let ports = |d| PORTS.map(|p| t.traverse(p, d));
+            Direction::all().all(|d| ports(d) == ports(Direction::North))
+        })
+        .unwrap();
+    let mut game = GameManager::with_tile_stack(all_tiles());
+    game.register_player("p0".into(), edge_position(2)).unwrap();
+    let puzzle = Puzzle {
+        board: game.board.clone(),
+        players: vec![PuzzlePlayer {
+            board_index: 0,
+            hand: vec![tile],
+        }],
+        tile_stack: Vec::new(),
+        dragon_holder: None,
+        objective: Objective::Survive,
+        solution: (0, Direction::North),
+    };
+    assert!(Direction::all().all(|d| puzzle.is_solution(0, d)));
+    assert_eq!(puzzle.solutions(), vec![(0, Direction::North)]);
+}
+
+#[test]
+fn test_survive_turns() {
+    use crate::agent::{Agent, AvoidSuddenDeathAgent};
+    use crate::board::edge_position;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::time::Instant;
+    let objective = Objective::SurviveTurns { turns: 2 };
+    let mut found = 0;
+    let mut deeper = 0;
+    for seed in 0..20 {
+        let mut game = GameManager::new(&mut StdRng::seed_from_u64(seed));
+        for (i, pos) in [2, 14, 26, 38].into_iter().enumerate() {
+            game.register_player(format!("p{}", i), edge_position(pos))
+                .unwrap();
+        }
+        while !game.is_over() {
+            if let Some(p) = Puzzle::from_game(&game, objective) {
+                assert_eq!(p.solutions(), vec![p.solution]);
+                // The next move from the hand survives too.
+                let (idx, facing) = p.solution;
+                let me = &p.players[0];
+                let mut board = p.board.clone();
+                board.play_tile(me.board_index, &me.hand[idx], facing);
+                let mut rest = me.hand.clone();
+                rest.remove(idx);
+                assert!(survives(&board, me.board_index, &rest, 1));
+                // Surviving just this turn is easier.
+                let one_turn = Puzzle {
+                    objective: Objective::Survive,
+                    ..p.clone()
+                };
+                if one_turn.solutions().len() > 1 {
+                    deeper += 1;
+                }
+                let names =
+                    (0..p.players.len()).map(|i| format!("p{}", i)).collect();
+                let mut solved = GameManager::from_puzzle(&p, names).unwrap();
+                solved.take_turn(idx, facing).unwrap();
+                assert_eq!(solved.puzzle_solved(), Some(true));
+                found += 1;
+            }
+            let obs = game.current_observation();
+            let (idx, facing) =
+                AvoidSuddenDeathAgent.choose_action(&obs, Instant::now());
+            game.take_turn(idx, facing).unwrap();
+        }
+    }
+    assert!(found > 0);
+    assert!(deeper > 0);
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+// How the winner is decided.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize,
+)]
+pub enum Scoring {
+    // The last player (or team) on the board wins.
+    #[default]
+    LastStanding,
+    // Play goes on until the tiles run out or everyone is eliminated, and
+    // the longest trail wins. Survivors get bonus points on top of the
+    // segments their trail crossed.
+    LongestPath {
+        survival_bonus: usize,
+    },
+}
+
+// Variations on the standard rules, chosen before the first move.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Rules {
+    // Team of each player, by board index. Teammates win together if any of
+    // them survives. Empty when everyone plays for themselves.
+    pub teams: Vec<usize>,
+    // Whether teammates can see each other's hands.
+    pub open_team_hands: bool,
+    // Whether everyone can see everyone's hands.
+    pub open_hands: bool,
+    // Whether everyone can see the order of the tiles left in the stack.
+    pub public_stack: bool,
+    // Whether players draft their starting tiles from a face-up pool, in
+    // snake order, instead of being dealt them.
+    pub draft: bool,
+    // Whether power tiles are mixed into the stack.
+    pub power_tiles: bool,
+    pub scoring: Scoring,
+}
+
+impl Rules {
+    // Splits players into teams of the given size, alternating between teams
+    // in the order players registered.
+    pub fn with_teams_of(team_size: usize, num_players: usize) -> Self {
+        let num_teams = num_players.div_ceil(team_size.max(1));
+        Rules {
+            teams: (0..num_players).map(|i| i % num_teams).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_team_game(&self) -> bool {
+        !self.teams.is_empty()
+    }
+
+    // Checks that the rules make sense for a game with this many players.
+    pub fn validate(&self, num_players: usize) -> Result<(), String> {
+        if !self.is_team_game() {
+            return Ok(());
+        }
+        if self.teams.len() != num_players {
+            return Err(format!(
+                "Got teams for {} players, but there are {}",
+                self.teams.len(),
+                num_players
+            ));
+        }
+        let first = self.teams[0];
+        if self.teams.iter().all(|&t| t == first) {
+            return Err("A team game needs at least two teams".to_owned());
+        }
+        // Only even matches, like 2v2 or 3v3.
+        let size =
+            |team: usize| self.teams.iter().filter(|&&t| t == team).count();
+        if size(first) < 2 || self.teams.iter().any(|&t| size(t) != size(first))
+        {
+            return Err("Teams need the same number of players, at least two"
+                .to_owned());
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_teams() {
+    let rules = Rules::with_teams_of(2, 4);
+    assert_eq!(rules.teams, [0, 1, 0, 1]);
+    assert!(rules.validate(4).is_ok());
+    assert!(rules.validate(5).is_err());
+    assert_eq!(Rules::with_teams_of(3, 6).teams, [0, 1, 0, 1, 0, 1]);
+    let one_team = Rules {
+        teams: vec![2, 2],
+        ..Default::default()
+    };
+    assert!(one_team.validate(2).is_err());
+    assert!(Rules::default().validate(3).is_ok());
+    let three_on_one = Rules {
+        teams: vec![0, 0, 1, 0],
+        ..Default::default()
+    };
+    assert!(three_on_one.validate(4).is_err());
+    let one_on_one = Rules {
+        teams: vec![0, 1],
+        ..Default::default()
+    };
+    assert!(one_on_one.validate(2).is_err());
+    assert!(Rules::with_teams_of(2, 6).validate(6).is_ok());
+}
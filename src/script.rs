@@ -0,0 +1,209 @@
+use crate::board::{EdgePos, NOT_READY, Position, edge_position};
+use crate::game::{GameManager, GameResult};
+use crate::rules::{Rules, Scoring};
+use crate::tiles::{Direction, all_tiles, power_tiles};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+// A fully determined game: where everyone sits, the order of the tiles, and
+// every move. Players are named p0, p1, ... in the order of `seats` and then
+// `interior_seats`; p0 moves first and play goes clockwise from them.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GameScript {
+    pub seats: Vec<EdgePos>,
+    // Indices into all_tiles() followed by power_tiles(), dealt from the end
+    // like GameManager's stack. Leaving tiles out makes for a shorter game.
+    pub tile_stack: Vec<usize>,
+    // Moves for whoever's turn it is, in order.
+    pub moves: Vec<(usize, Direction)>,
+    #[serde(default)]
+    pub rules: Rules,
+    // Picks from the draft pool, when the rules call for a draft.
+    #[serde(default)]
+    pub draft_picks: Vec<usize>,
+    // Starting positions inside the board, between two cells.
+    #[serde(default)]
+    pub interior_seats: Vec<Position>,
+}
+
+// The state of a scripted game after its last move.
+pub struct ScriptOutcome {
+    pub game: GameManager,
+    // Set if the game ended.
+    pub result: Option<GameResult>,
+}
+
+impl GameScript {
+    pub fn run(&self) -> Result<ScriptOutcome, String> {
+        let tiles: Vec<_> =
+            all_tiles().into_iter().chain(power_tiles()).collect();
+        let mut stack = Vec::with_capacity(self.tile_stack.len());
+        for (i, &idx) in self.tile_stack.iter().enumerate() {
+            let tile = tiles.get(idx).ok_or(format!("No tile {}", idx))?;
+            if self.tile_stack[..i].contains(&idx) {
+                return Err(format!("Tile {} is in the stack twice", idx));
+            }
+            stack.push(*tile);
+        }
+        let mut game = GameManager::with_tile_stack(stack);
+        for (i, &seat) in self.seats.iter().enumerate() {
+            if !(0..NOT_READY).contains(&seat) {
+                return Err(format!("Invalid seat: {}", seat));
+            }
+            game.register_player(format!("p{}", i), edge_position(seat))?;
+        }
+        for (i, pos) in self.interior_seats.iter().enumerate() {
+            let name = format!("p{}", self.seats.len() + i);
+            game.register_player(name, pos.clone())?;
+        }
+        if game.alive_players.is_empty() {
+            return Err("No players".to_owned());
+        }
+        game.set_rules(self.rules.clone())?;
+        for &pick in self.draft_picks.iter() {
+            game.draft_tile(pick)?;
+        }
+        if game.is_drafting() {
+            return Err("The draft isn't finished".to_owned());
+        }
+        let mut result = None;
+        for (turn, &(tile_idx, facing)) in self.moves.iter().enumerate() {
+            if result.is_some() || game.is_over() {
+                return Err(format!("Move {} is after the game ended", turn));
+            }
+            let hand_size = game.current_player().tiles_in_hand.len();
+            if tile_idx >= hand_size {
+                return Err(format!(
+                    "Move {} plays tile {} from a hand of {}",
+                    turn, tile_idx, hand_size
+                ));
+            }
+            result = game.take_turn(tile_idx, facing)?;
+        }
+        Ok(ScriptOutcome { game, result })
+    }
+}
+
+impl ScriptOutcome {
+    // A plain-text summary of the final state, stable enough to diff.
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let tiles: Vec<_> =
+            all_tiles().into_iter().chain(power_tiles()).collect();
+        match &self.result {
+            None => out.push_str("winners: (game not over)\n"),
+            Some(r) if r.winners().is_empty() => {
+                out.push_str("winners: (none)\n")
+            }
+            Some(r) => {
+                writeln!(out, "winners: {}", r.winners().join(", ")).unwrap()
+            }
+        }
+        let scores = self.game.player_scores();
+        let scores: Vec<String> = scores
+            .iter()
+            .enumerate()
+            .map(|(i, s)| format!("p{}={}", i, s))
+            .collect();
+        writeln!(out, "scores: {}", scores.join(" ")).unwrap();
+        let places: Vec<String> = self
+            .game
+            .standings()
+            .iter()
+            .map(|s| format!("{}={}", s.username, s.placement))
+            .collect();
+        writeln!(out, "places: {}", places.join(" ")).unwrap();
+        if let Scoring::LongestPath { .. } = self.game.rules().scoring {
+            let points: Vec<String> = (0..self.game.board.players.len())
+                .map(|i| format!("p{}={}", i, self.game.path_points(i)))
+                .collect();
+            writeln!(out, "points: {}", points.join(" ")).unwrap();
+        }
+        // Each cell shows the tile index and the first letter of its facing.
+        // Power tiles are numbered after the standard ones.
+        out.push_str("board:\n");
+        for row in 0..6 {
+            let mut line = String::from(" ");
+            for col in 0..6 {
+                let pos = Position {
+                    row,
+                    col,
+                    port: crate::tiles::Port::A,
+                    alive: true,
+                };
+                let cell = match self.game.board.get_tile(&pos) {
+                    Some(Some((tile, facing))) => {
+                        let idx = tiles.iter().position(|t| t == tile).unwrap();
+                        let facing = format!("{:?}", facing);
+                        format!("{}{}", idx, &facing[..1])
+                    }
+                    _ => ".".to_owned(),
+                };
+                write!(line, " {:>4}", cell).unwrap();
+            }
+            writeln!(out, "{}", line.trim_end()).unwrap();
+        }
+        out.push_str("trails:\n");
+        for (i, trail) in self.game.board.players.iter().enumerate() {
+            let steps: Vec<String> = trail
+                .iter()
+                .map(|p| format!("{},{},{:?}", p.row, p.col, p.port))
+                .collect();
+            let alive = trail.last().unwrap().alive;
+            let status = if alive { "alive" } else { "dead" };
+            writeln!(out, "  p{} ({}): {}", i, status, steps.join(" "))
+                .unwrap();
+        }
+        out
+    }
+}
+
+#[test]
+fn test_script_errors() {
+    let script = GameScript {
+        seats: vec![0, 20],
+        tile_stack: (0..35).collect(),
+        moves: vec![(3, Direction::North)],
+        rules: Rules::default(),
+        draft_picks: vec![],
+        interior_seats: vec![],
+    };
+    assert!(script.run().err().unwrap().contains("hand of 3"));
+    let script = GameScript {
+        seats: vec![0, 48],
+        tile_stack: (0..35).collect(),
+        moves: vec![],
+        rules: Rules::default(),
+        draft_picks: vec![],
+        interior_seats: vec![],
+    };
+    assert!(script.run().is_err());
+    let script = GameScript {
+        seats: vec![0, 20],
+        tile_stack: vec![1, 2, 1],
+        moves: vec![],
+        rules: Rules::default(),
+        draft_picks: vec![],
+        interior_seats: vec![],
+    };
+    assert!(script.run().is_err());
+    // Power tiles are numbered after the standard ones, and the two bridges
+    // are separate tiles.
+    let script = GameScript {
+        seats: vec![0, 20],
+        tile_stack: (0..39).collect(),
+        moves: vec![],
+        rules: Rules::default(),
+        draft_picks: vec![],
+        interior_seats: vec![],
+    };
+    let outcome = script.run().unwrap();
+    assert_eq!(outcome.game.tiles_remaining(), 39 - 6);
+    // The last six tiles are dealt, so both players hold power tiles.
+    let dealt = outcome
+        .game
+        .alive_players
+        .iter()
+        .flat_map(|p| &p.tiles_in_hand);
+    assert_eq!(dealt.filter(|t| t.effect.is_some()).count(), 4);
+}
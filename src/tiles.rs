@@ -19,6 +19,15 @@ impl Direction {
         .iter()
         .copied()
     }
+    // The facing after a quarter turn clockwise.
+    pub(crate) fn clockwise(&self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
     pub fn grid_offsets(&self) -> (i8, i8) {
         match self {
             Direction::North => (-1, 0),
@@ -94,6 +103,19 @@ impl Port {
             Port::H => Port::C,
         }
     }
+    // The other port on the same side of the tile.
+    fn sibling(&self) -> Self {
+        match self {
+            Port::A => Port::B,
+            Port::B => Port::A,
+            Port::C => Port::D,
+            Port::D => Port::C,
+            Port::E => Port::F,
+            Port::F => Port::E,
+            Port::G => Port::H,
+            Port::H => Port::G,
+        }
+    }
     pub fn facing_side(&self) -> Direction {
         match self {
             Port::A | Port::B => Direction::North,
@@ -104,307 +126,263 @@ impl Port {
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+// What a power tile does when it's played.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+pub enum Effect {
+    // Lets the player turn one tile next to it a quarter turn clockwise. Only
+    // trails that reach that tile afterwards take its new paths.
+    Spinner,
+    // Carries every path straight across, but traffic heading down or left
+    // crosses over into the other lane. Nobody coming back over it can run
+    // into a trail that went the other way. Spinners can't turn it.
+    Bridge,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize, Serialize)]
 pub struct Tile {
     layout: [(Port, Port); 4],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effect: Option<Effect>,
 }
 
 impl Tile {
+    const fn new(layout: [(Port, Port); 4]) -> Self {
+        Tile {
+            layout,
+            effect: None,
+        }
+    }
     pub fn traverse(&self, from: Port, facing: Direction) -> Port {
         let start = facing.normalize_port(from);
-        for (p1, p2) in self.layout.iter() {
-            if start == *p1 {
-                return facing.unnormalize_port(*p2);
-            } else if start == *p2 {
-                return facing.unnormalize_port(*p1);
-            }
+        let end = self
+            .layout
+            .iter()
+            .find_map(|&(p1, p2)| {
+                (start == p1).then_some(p2).or((start == p2).then_some(p1))
+            })
+            .unwrap_or_else(|| {
+                panic!("Unreachable path: start={:?}, tile={:?}", start, self)
+            });
+        let crossing = matches!(start, Port::A | Port::B | Port::C | Port::D);
+        if self.effect == Some(Effect::Bridge) && crossing {
+            return facing.unnormalize_port(end.sibling());
         }
-        panic!("Unreachable path: start={:?}, tile={:?}", start, self);
+        facing.unnormalize_port(end)
     }
 }
 
 pub fn all_tiles() -> Vec<Tile> {
     vec![
-        Tile {
-            layout: [
-                (Port::A, Port::E),
-                (Port::B, Port::F),
-                (Port::C, Port::H),
-                (Port::D, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::E),
-                (Port::B, Port::F),
-                (Port::C, Port::G),
-                (Port::D, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::F),
-                (Port::B, Port::E),
-                (Port::C, Port::H),
-                (Port::D, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::E),
-                (Port::B, Port::D),
-                (Port::C, Port::G),
-                (Port::F, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::H),
-                (Port::B, Port::C),
-                (Port::D, Port::E),
-                (Port::F, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::E),
-                (Port::B, Port::C),
-                (Port::D, Port::H),
-                (Port::F, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::E),
-                (Port::B, Port::C),
-                (Port::D, Port::G),
-                (Port::F, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::D),
-                (Port::B, Port::G),
-                (Port::C, Port::F),
-                (Port::E, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::D),
-                (Port::B, Port::F),
-                (Port::C, Port::G),
-                (Port::E, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::D),
-                (Port::B, Port::E),
-                (Port::C, Port::H),
-                (Port::F, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::D),
-                (Port::B, Port::E),
-                (Port::C, Port::G),
-                (Port::F, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::D),
-                (Port::B, Port::C),
-                (Port::E, Port::H),
-                (Port::F, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::C),
-                (Port::B, Port::H),
-                (Port::D, Port::F),
-                (Port::E, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::C),
-                (Port::B, Port::H),
-                (Port::D, Port::E),
-                (Port::F, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::C),
-                (Port::B, Port::G),
-                (Port::D, Port::F),
-                (Port::E, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::C),
-                (Port::B, Port::G),
-                (Port::D, Port::E),
-                (Port::F, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::C),
-                (Port::B, Port::F),
-                (Port::D, Port::H),
-                (Port::E, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::C),
-                (Port::B, Port::F),
-                (Port::D, Port::G),
-                (Port::E, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::C),
-                (Port::B, Port::E),
-                (Port::D, Port::H),
-                (Port::F, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::C),
-                (Port::B, Port::E),
-                (Port::D, Port::G),
-                (Port::F, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::C),
-                (Port::B, Port::D),
-                (Port::E, Port::H),
-                (Port::F, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::C),
-                (Port::B, Port::D),
-                (Port::E, Port::G),
-                (Port::F, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::H),
-                (Port::D, Port::G),
-                (Port::E, Port::F),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::H),
-                (Port::D, Port::F),
-                (Port::E, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::H),
-                (Port::D, Port::E),
-                (Port::F, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::G),
-                (Port::D, Port::H),
-                (Port::E, Port::F),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::G),
-                (Port::D, Port::F),
-                (Port::E, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::G),
-                (Port::D, Port::E),
-                (Port::F, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::F),
-                (Port::D, Port::H),
-                (Port::E, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::F),
-                (Port::D, Port::G),
-                (Port::E, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::E),
-                (Port::D, Port::H),
-                (Port::F, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::E),
-                (Port::D, Port::G),
-                (Port::F, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::D),
-                (Port::E, Port::H),
-                (Port::F, Port::G),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::D),
-                (Port::E, Port::G),
-                (Port::F, Port::H),
-            ],
-        },
-        Tile {
-            layout: [
-                (Port::A, Port::B),
-                (Port::C, Port::D),
-                (Port::E, Port::F),
-                (Port::G, Port::H),
-            ],
-        },
+        Tile::new([
+            (Port::A, Port::E),
+            (Port::B, Port::F),
+            (Port::C, Port::H),
+            (Port::D, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::E),
+            (Port::B, Port::F),
+            (Port::C, Port::G),
+            (Port::D, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::F),
+            (Port::B, Port::E),
+            (Port::C, Port::H),
+            (Port::D, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::E),
+            (Port::B, Port::D),
+            (Port::C, Port::G),
+            (Port::F, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::H),
+            (Port::B, Port::C),
+            (Port::D, Port::E),
+            (Port::F, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::E),
+            (Port::B, Port::C),
+            (Port::D, Port::H),
+            (Port::F, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::E),
+            (Port::B, Port::C),
+            (Port::D, Port::G),
+            (Port::F, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::D),
+            (Port::B, Port::G),
+            (Port::C, Port::F),
+            (Port::E, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::D),
+            (Port::B, Port::F),
+            (Port::C, Port::G),
+            (Port::E, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::D),
+            (Port::B, Port::E),
+            (Port::C, Port::H),
+            (Port::F, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::D),
+            (Port::B, Port::E),
+            (Port::C, Port::G),
+            (Port::F, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::D),
+            (Port::B, Port::C),
+            (Port::E, Port::H),
+            (Port::F, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::C),
+            (Port::B, Port::H),
+            (Port::D, Port::F),
+            (Port::E, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::C),
+            (Port::B, Port::H),
+            (Port::D, Port::E),
+            (Port::F, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::C),
+            (Port::B, Port::G),
+            (Port::D, Port::F),
+            (Port::E, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::C),
+            (Port::B, Port::G),
+            (Port::D, Port::E),
+            (Port::F, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::C),
+            (Port::B, Port::F),
+            (Port::D, Port::H),
+            (Port::E, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::C),
+            (Port::B, Port::F),
+            (Port::D, Port::G),
+            (Port::E, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::C),
+            (Port::B, Port::E),
+            (Port::D, Port::H),
+            (Port::F, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::C),
+            (Port::B, Port::E),
+            (Port::D, Port::G),
+            (Port::F, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::C),
+            (Port::B, Port::D),
+            (Port::E, Port::H),
+            (Port::F, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::C),
+            (Port::B, Port::D),
+            (Port::E, Port::G),
+            (Port::F, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::H),
+            (Port::D, Port::G),
+            (Port::E, Port::F),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::H),
+            (Port::D, Port::F),
+            (Port::E, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::H),
+            (Port::D, Port::E),
+            (Port::F, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::G),
+            (Port::D, Port::H),
+            (Port::E, Port::F),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::G),
+            (Port::D, Port::F),
+            (Port::E, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::G),
+            (Port::D, Port::E),
+            (Port::F, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::F),
+            (Port::D, Port::H),
+            (Port::E, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::F),
+            (Port::D, Port::G),
+            (Port::E, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::E),
+            (Port::D, Port::H),
+            (Port::F, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::E),
+            (Port::D, Port::G),
+            (Port::F, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::D),
+            (Port::E, Port::H),
+            (Port::F, Port::G),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::D),
+            (Port::E, Port::G),
+            (Port::F, Port::H),
+        ]),
+        Tile::new([
+            (Port::A, Port::B),
+            (Port::C, Port::D),
+            (Port::E, Port::F),
+            (Port::G, Port::H),
+        ]),
     ]
 }
 
@@ -412,3 +390,52 @@ pub fn all_tiles() -> Vec<Tile> {
 fn test_all_tiles() {
     assert_eq!(all_tiles().len(), 35);
 }
+
+// Tiles with special effects, for the power tiles variant.
+pub fn power_tiles() -> Vec<Tile> {
+    let tiles = all_tiles();
+    let spinner = |tile: Tile| Tile {
+        effect: Some(Effect::Spinner),
+        ..tile
+    };
+    let bridge = Tile {
+        layout: [
+            (Port::A, Port::F),
+            (Port::B, Port::E),
+            (Port::C, Port::H),
+            (Port::D, Port::G),
+        ],
+        effect: Some(Effect::Bridge),
+    };
+    vec![spinner(tiles[0]), spinner(tiles[1]), bridge, bridge]
+}
+
+#[test]
+fn test_power_tiles() {
+    let tiles = power_tiles();
+    assert_eq!(tiles.len(), 4);
+    assert!(tiles.iter().all(|t| t.effect.is_some()));
+    assert!(tiles.iter().all(|t| !all_tiles().contains(t)));
+    // Bridges go straight across whichever way they face, and never lead
+    // back the way another trail came.
+    let bridge = tiles[3];
+    let ports = [
+        Port::A,
+        Port::B,
+        Port::C,
+        Port::D,
+        Port::E,
+        Port::F,
+        Port::G,
+        Port::H,
+    ];
+    for dir in Direction::all() {
+        for port in ports {
+            let across = bridge.traverse(port, dir);
+            assert_eq!(across.facing_side(), port.flip().facing_side());
+            assert_ne!(bridge.traverse(across, dir), port);
+        }
+    }
+    assert_eq!(bridge.traverse(Port::A, Direction::North), Port::E);
+    assert_eq!(bridge.traverse(Port::E, Direction::North), Port::B);
+}
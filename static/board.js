@@ -15,18 +15,33 @@ const PLAYER_COLORS = [
   'red', 'blue', 'green', 'purple', 'magenta', 'cyan', 'white', 'limegreen',
   'black', 'brown', 'gray',
 ];
+// In team games, each team gets shades of one color.
+const TEAM_COLORS = [
+  ['red', 'darkred', 'salmon', 'orange', 'crimson', 'pink'],
+  ['blue', 'navy', 'cyan', 'steelblue', 'purple', 'teal'],
+];
 
 let playerPositions = [];
 let rotations = [];
+let handTiles = [];
 let gameId = 0;
+let isPuzzle = false;
+// Team of each player by board index, or empty if there are no teams.
+let teams = [];
+// When the current player runs out of time, in ms since the epoch.
+let clockDeadline = null;
 
 function bodyLoaded() {
   gameId = (new URL(window.location)).searchParams.get('id');
-  fetchJson(`/board/${gameId}`, (board) => {
-    if (renderBoard(board)) {
-      fetchJson(`/hand/${gameId}`, renderHand);
-    }
+  fetchJson(`/rules/${gameId}`, (rules) => {
+    teams = rules.teams || [];
+    fetchJson(`/board/${gameId}`, (board) => {
+      if (renderBoard(board)) {
+        fetchJson(`/hand/${gameId}`, renderHand);
+      }
+    });
   });
+  fetchJson(`/puzzle/${gameId}`, renderGoal);
 
   const ws = new WebSocket(`ws://${location.host}/ws/${gameId}`);
   ws.onopen = () => console.log('Opened WS connection.');
@@ -34,23 +49,85 @@ function bodyLoaded() {
     const msg = JSON.parse(event.data);
     console.log('Got WS message:', msg);
     if (msg.action === 'Update') {
+      setClock(msg.clock);
       if (renderBoard(msg.board)) {
         fetchJson(`/hand/${gameId}`, renderHand);
       }
     } else if (msg.action === 'GameOver') {
       renderBoard(msg.board);
+      setClock(null);
       document.querySelector('.hand').innerHTML = '';
       // TODO: show a proper game over page
-      if (msg.winner) {
-        alert(`Game over: ${msg.winner} is the winner!`);
+      const result = msg.result;
+      if (isPuzzle) {
+        alert(result.outcome === 'Winner'
+          ? 'Puzzle solved!' : 'Not quite, try again tomorrow!');
+      } else if (result.outcome === 'Winner') {
+        alert(`Game over: ${result.player} is the winner!`);
+      } else if (result.outcome === 'SharedWin') {
+        alert(`Game over: ${result.players.join(' and ')} share the win!`);
       } else {
-        alert('Game over: everyone lost!');
+        alert('Game over: everyone was eliminated at once!');
       }
     } else if (msg.action === 'Error') {
       renderError(msg.message);
     }
   }
   ws.onclose = () => console.log('Closed WS connection.');
+  setInterval(renderClock, 1000);
+}
+
+function renderGoal(objective) {
+  let goal;
+  if (objective === 'Survive') {
+    goal = 'Find the only move that survives.';
+  } else if (objective.Eliminate) {
+    goal = `Survive and knock out ${objective.Eliminate.opponents} opponents.`;
+  } else if (objective.EliminateTarget) {
+    const color = playerColor(objective.EliminateTarget.board_index);
+    goal = `Survive and knock out the ${color} player.`;
+  } else if (objective.ReachCell) {
+    const { row, col } = objective.ReachCell;
+    goal = `Survive and pass through row ${row + 1}, column ${col + 1}.`;
+  } else if (objective.SurviveTurns) {
+    const { turns } = objective.SurviveTurns;
+    goal = `Find the only move that survives your next ${turns} turns.`;
+  } else {
+    return;
+  }
+  isPuzzle = true;
+  document.getElementById('goal').innerText = `Puzzle: ${goal}`;
+}
+
+function setClock(clock) {
+  const elt = document.getElementById('clock');
+  if (!clock) {
+    clockDeadline = null;
+    elt.innerText = '';
+    return;
+  }
+  clockDeadline = Date.now() + clock.turn_remaining_ms;
+  elt.dataset.player = clock.player;
+  elt.dataset.bankDeadline = clock.game_remaining_ms
+    ? Date.now() + clock.game_remaining_ms[clock.player] : '';
+  renderClock();
+}
+
+function renderClock() {
+  if (clockDeadline === null) return;
+  const elt = document.getElementById('clock');
+  const left = formatTime(Math.max(0, clockDeadline - Date.now()));
+  let bank = '';
+  if (elt.dataset.bankDeadline) {
+    const ms = Math.max(0, elt.dataset.bankDeadline - Date.now());
+    bank = ` (${formatTime(ms)} left in game)`;
+  }
+  elt.innerText = `${elt.dataset.player} has ${left} to move${bank}`;
+}
+
+function formatTime(ms) {
+  const secs = Math.ceil(ms / 1000);
+  return `${Math.floor(secs / 60)}:${String(secs % 60).padStart(2, '0')}`;
 }
 
 function renderError(message) {
@@ -58,15 +135,44 @@ function renderError(message) {
 }
 
 function playTile(tileIdx) {
+  const spinner = handTiles[tileIdx]?.effect === 'Spinner';
+  const spin = spinner && document.getElementById('spin').value || null;
   fetch('/play', {
     method: 'POST',
     headers: { 'Content-Type': 'application/json' },
     body: JSON.stringify({
-      game_id: +gameId, idx: tileIdx, facing: rotations[tileIdx],
+      game_id: +gameId, idx: tileIdx, facing: rotations[tileIdx], spin,
     }),
   });
 }
 
+function draftTile(poolIdx) {
+  renderError('');
+  fetch('/draft', {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({ game_id: +gameId, idx: poolIdx }),
+  });
+}
+
+function resign() {
+  if (!confirm('Resign from this game?')) return;
+  renderError('');
+  fetch(`/resign/${gameId}`, { method: 'POST' });
+}
+
+function requestHint() {
+  fetchJson(`/hint/${gameId}`, (hint) => {
+    if (!hint.explanation) return renderError(hint);
+    renderError('');
+    while (rotations[hint.tile_idx] !== hint.facing) {
+      rotateTile(hint.tile_idx);
+    }
+    document.getElementById('hint').innerText =
+      `Try tile ${hint.tile_idx + 1}: ${hint.explanation}`;
+  });
+}
+
 function rotateTile(tileIdx) {
   let new_facing = ROTATE[rotations[tileIdx]];
   rotations[tileIdx] = new_facing;
@@ -78,13 +184,17 @@ function rotateTile(tileIdx) {
 
 function renderHand(hand) {
   if (!hand.username) return renderError(hand);
+  document.getElementById('hint').innerText = '';
   let subtitle = document.getElementsByClassName('subtitle')[0];
   subtitle.innerText =
-    `${hand.username}'s Tiles (${PLAYER_COLORS[hand.board_index]})`;
+    `${hand.username}'s Tiles (${playerColor(hand.board_index)})`;
   if (!document.querySelector('.board > .target')) {
     let [row, col] = playerPositions[hand.board_index];
     document.querySelector(`.board > .r${row}.c${col}`).classList.add('target');
   }
+  handTiles = hand.tiles_in_hand;
+  document.getElementById('spin-choice').hidden =
+    !handTiles.some(t => t.effect === 'Spinner');
   let handContainer = document.getElementsByClassName('hand')[0];
   const handSize = hand.tiles_in_hand.length;
   for (let idx = 0; idx < handSize; ++idx) {
@@ -121,6 +231,63 @@ function renderHand(hand) {
   while (handContainer.children.length > handSize) {
     handContainer.removeChild(handContainer.lastChild);
   }
+  renderOtherHands(hand.other_hands || [], hand.upcoming_tiles);
+  renderDraftPool(hand.draft_pool || []);
+  setClock(hand.clock);
+}
+
+function renderDraftPool(pool) {
+  const container = document.getElementById('draft-pool');
+  container.innerHTML = '';
+  if (pool.length === 0) return;
+  const label = document.createElement('div');
+  label.innerText = 'Draft a tile:';
+  container.appendChild(label);
+  pool.forEach((tile, idx) => {
+    const wrap = document.createElement('div');
+    wrap.classList.add('choice');
+    const elt = document.createElement('div');
+    elt.classList.add('tile');
+    elt.appendChild(renderTile(tile, 'North'));
+    elt.onclick = () => draftTile(idx);
+    wrap.appendChild(elt);
+    container.appendChild(wrap);
+  });
+}
+
+function renderOtherHands(otherHands, upcomingTiles) {
+  const container = document.getElementById('other-hands');
+  container.innerHTML = '';
+  for (const mate of otherHands) {
+    const label = document.createElement('div');
+    label.innerText = `${mate.username}'s Tiles (${playerColor(mate.board_index)})`;
+    container.appendChild(label);
+    for (const tile of mate.tiles_in_hand) {
+      const elt = document.createElement('div');
+      elt.classList.add('tile');
+      elt.appendChild(renderTile(tile, 'North'));
+      container.appendChild(elt);
+    }
+  }
+  if (upcomingTiles) {
+    const label = document.createElement('div');
+    label.innerText = `Upcoming Tiles (${upcomingTiles.length})`;
+    container.appendChild(label);
+    for (const tile of upcomingTiles) {
+      const elt = document.createElement('div');
+      elt.classList.add('tile');
+      elt.appendChild(renderTile(tile, 'North'));
+      container.appendChild(elt);
+    }
+  }
+}
+
+function playerColor(idx) {
+  if (teams.length === 0) return PLAYER_COLORS[idx];
+  const team = teams[idx];
+  const shade = teams.slice(0, idx).filter(t => t === team).length;
+  const palette = TEAM_COLORS[team % TEAM_COLORS.length];
+  return palette[shade % palette.length];
 }
 
 function renderBoard(board) {
@@ -188,16 +355,18 @@ function renderBoard(board) {
   }
   // Update player positions.
   for (const [idx, playerTrail] of board.players.entries()) {
-    const color = PLAYER_COLORS[idx];
+    const color = playerColor(idx);
     let tileDiv;
-    for (const pos of playerTrail) {
+    for (const [i, pos] of playerTrail.entries()) {
       tileDiv = boardContainer.querySelector(`.r${pos.row}.c${pos.col}`);
-      if (tileDiv.classList.contains('played')) {
-        // Add an svg path for the trail.
+      if (tileDiv.classList.contains('played') && i > 0) {
+        // Add an svg path for the trail, from where it came in to where it
+        // left. Bridge lanes don't run both ways, so the tile's layout isn't
+        // enough to tell.
         let [gridTile, facing] = board.grid[pos.row][pos.col];
         let origPort = unnormalizePort(pos.port, facing);
-        let [p0, p1] = gridTile.layout.find(p => p.includes(origPort));
-        let trailPath = makePath((p0 == origPort) ? pathCode(p0, p1) : pathCode(p1, p0));
+        let entry = PORT_FLIPS[playerTrail[i - 1].port];
+        let trailPath = makePath(pathCode(unnormalizePort(entry, facing), origPort));
         trailPath.setAttribute('stroke', color);
         trailPath.setAttribute('stroke-width', 5);
         trailPath.setAttribute('stroke-dasharray', '15,5');
@@ -44,16 +44,17 @@ function renderLobby(data) {
   lobbyDiv.innerHTML = '';
   const table = document.createElement('table');
   const header = document.createElement('tr');
-  header.innerHTML = '<th>Player</th><th>Seat</th>';
+  header.innerHTML = '<th>Player</th><th>Seat</th><th>Team</th>';
   table.appendChild(header);
   for (let i = 0; i < data.max_num_players; i++) {
     const username = data.names[i];
     const seat = data.start_positions[i];
+    const team = data.teams[i];
 
     const tableRow = document.createElement('tr');
     if (!username) {
       const openCell = document.createElement('td');
-      openCell.colSpan = 2;
+      openCell.colSpan = 3;
       if (isInLobby) {
         openCell.innerText = 'Waiting for player...';
       } else {
@@ -69,8 +70,15 @@ function renderLobby(data) {
       } else {
         seatCell.innerText = seat === 48 ? 'Not seated' : `Seat ${seat}`;
       }
+      const teamCell = document.createElement('td');
+      if (username === USERNAME) {
+        renderTeamPicker(teamCell, team);
+      } else {
+        teamCell.innerText = team === null ? '' : `Team ${team + 1}`;
+      }
       tableRow.appendChild(nameCell);
       tableRow.appendChild(seatCell);
+      tableRow.appendChild(teamCell);
     }
     table.appendChild(tableRow);
   }
@@ -91,6 +99,44 @@ function renderLobby(data) {
     removePlayerButton.onclick = () => setNumPlayers(data.max_num_players - 1);
     hostDiv.appendChild(removePlayerButton);
   }
+  const hintsLabel = document.createElement('label');
+  const hintsBox = document.createElement('input');
+  hintsBox.type = 'checkbox';
+  hintsBox.checked = data.settings.allow_hints;
+  hintsBox.onchange = () => setAllowHints(hintsBox.checked);
+  hintsLabel.appendChild(hintsBox);
+  hintsLabel.append(' Allow hints');
+  hostDiv.appendChild(hintsLabel);
+  const options = [
+    ['open_team_hands', 'Teammates see each other\'s tiles'],
+    ['open_hands', 'Everyone sees everyone\'s tiles'],
+    ['public_stack', 'Everyone sees the upcoming tiles'],
+    ['draft', 'Draft starting tiles'],
+    ['power_tiles', 'Add power tiles'],
+    ['eliminate_on_timeout', 'Knock out players who run out of time'],
+  ];
+  for (const [option, text] of options) {
+    const label = document.createElement('label');
+    const box = document.createElement('input');
+    box.type = 'checkbox';
+    box.checked = data.settings[option];
+    box.onchange = () => setOption(option, box.checked);
+    label.appendChild(box);
+    label.append(` ${text}`);
+    hostDiv.appendChild(label);
+  }
+  renderClockInputs(hostDiv, data.settings);
+  const spacingLabel = document.createElement('label');
+  const spacingInput = document.createElement('input');
+  spacingInput.type = 'number';
+  spacingInput.min = 0;
+  spacingInput.max = 24;
+  spacingInput.value = data.settings.start_spacing;
+  spacingInput.onchange = () => setSpacing(spacingInput.value);
+  spacingLabel.appendChild(spacingInput);
+  spacingLabel.append(' Minimum seats between players');
+  hostDiv.appendChild(spacingLabel);
+  renderScoringInputs(hostDiv, data.settings.scoring);
   if (isInLobby) {
     const startGameButton = document.createElement('button');
     startGameButton.innerText = 'Start Game';
@@ -100,6 +146,57 @@ function renderLobby(data) {
   lobbyDiv.appendChild(hostDiv);
 }
 
+// Last player standing, or longest path with a bonus for surviving.
+function renderScoringInputs(parent, scoring) {
+  const longest = scoring.LongestPath;
+  const scoringSelect = document.createElement('select');
+  for (const [value, text] of [
+    ['LastStanding', 'Last player standing wins'],
+    ['LongestPath', 'Longest path wins'],
+  ]) {
+    const option = document.createElement('option');
+    option.value = value;
+    option.innerText = text;
+    option.selected = (value === 'LongestPath') === !!longest;
+    scoringSelect.appendChild(option);
+  }
+  const bonusInput = document.createElement('input');
+  bonusInput.type = 'number';
+  bonusInput.min = 0;
+  bonusInput.max = 100;
+  bonusInput.value = longest ? longest.survival_bonus : 0;
+  bonusInput.disabled = !longest;
+  const update = () => setScoring(scoringSelect.value, bonusInput.value);
+  scoringSelect.onchange = update;
+  bonusInput.onchange = update;
+  const bonusLabel = document.createElement('label');
+  bonusLabel.appendChild(bonusInput);
+  bonusLabel.append(' Bonus points for surviving');
+  parent.appendChild(scoringSelect);
+  parent.appendChild(bonusLabel);
+}
+
+// Zero means no limit for either clock.
+function renderClockInputs(parent, settings) {
+  const turnInput = document.createElement('input');
+  const gameInput = document.createElement('input');
+  for (const input of [turnInput, gameInput]) {
+    input.type = 'number';
+    input.min = 0;
+    input.onchange = () => setClock(turnInput.value, gameInput.value * 60);
+  }
+  turnInput.value = settings.turn_seconds;
+  gameInput.value = settings.game_seconds / 60;
+  for (const [input, text] of [
+    [turnInput, 'Seconds per turn'], [gameInput, 'Minutes per player'],
+  ]) {
+    const label = document.createElement('label');
+    label.appendChild(input);
+    label.append(` ${text}`);
+    parent.appendChild(label);
+  }
+}
+
 function renderTakeSeatForm(parent, seat) {
   parent.innerHTML = 'Take a seat: ';
   const seatForm = document.createElement('form');
@@ -116,6 +213,20 @@ function renderTakeSeatForm(parent, seat) {
   parent.appendChild(seatForm);
 }
 
+// Team 2 means no team, for a free-for-all.
+function renderTeamPicker(parent, team) {
+  const select = document.createElement('select');
+  for (const [value, label] of [[2, 'No team'], [0, 'Team 1'], [1, 'Team 2']]) {
+    const option = document.createElement('option');
+    option.value = value;
+    option.innerText = label;
+    option.selected = (team === null ? 2 : team) === value;
+    select.appendChild(option);
+  }
+  select.onchange = () => setTeam(select.value);
+  parent.appendChild(select);
+}
+
 function renderError(message) {
   document.getElementById('error').innerText = message;
 }
@@ -138,4 +249,41 @@ function startGame() {
 function setNumPlayers(numPlayers) {
   renderError('');
   fetch(`/lobby_size/${LOBBY_CODE}/${numPlayers}`, { method: 'POST' });
-}
\ No newline at end of file
+}
+
+function setAllowHints(allow) {
+  renderError('');
+  fetch(`/lobby_hints/${LOBBY_CODE}/${allow}`, { method: 'POST' });
+}
+
+function setTeam(team) {
+  renderError('');
+  fetch(`/lobby_team/${LOBBY_CODE}/${team}`, { method: 'POST' });
+}
+
+function setOption(option, value) {
+  renderError('');
+  fetch(`/lobby_option/${LOBBY_CODE}/${option}/${value}`, { method: 'POST' });
+}
+
+function setClock(turnSeconds, gameSeconds) {
+  renderError('');
+  fetch(`/lobby_clock/${LOBBY_CODE}/${turnSeconds}/${gameSeconds}`,
+    { method: 'POST' });
+}
+
+function setSpacing(spacing) {
+  renderError('');
+  fetch(`/lobby_spacing/${LOBBY_CODE}/${spacing}`, { method: 'POST' });
+}
+
+function setScoring(scoring, bonus) {
+  renderError('');
+  const body = scoring === 'LongestPath'
+    ? { LongestPath: { survival_bonus: +bonus } } : scoring;
+  fetch(`/lobby_scoring/${LOBBY_CODE}`, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(body),
+  });
+}
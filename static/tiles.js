@@ -8,16 +8,46 @@ function renderTile(tile, facing, svg) {
     svg.classList.add(facing);
   } else {
     svg.classList.value = facing;
-    svg.removeChild(svg.firstChild);
+    svg.replaceChildren();
   }
   let code = '';
   for (const [src, dst] of tile.layout) {
     code += pathCode(src, dst);
   }
+  if (tile.effect === 'Bridge') {
+    // Traffic heading down or left crosses over into the other lane.
+    for (const [src, dst] of [['A', 'E'], ['B', 'F'], ['C', 'G'], ['D', 'H']]) {
+      code += pathCode(src, dst);
+    }
+  }
   svg.appendChild(makePath(code));
+  if (tile.effect) {
+    svg.appendChild(makeEffectMark(tile.effect));
+  }
   return svg;
 }
 
+// Power tiles get a badge in the middle: a ring for spinners, a plank for
+// bridges.
+function makeEffectMark(effect) {
+  const ns = 'http://www.w3.org/2000/svg';
+  let mark;
+  if (effect === 'Spinner') {
+    mark = document.createElementNS(ns, 'circle');
+    mark.setAttribute('cx', '49.5');
+    mark.setAttribute('cy', '49.5');
+    mark.setAttribute('r', '12');
+  } else {
+    mark = document.createElementNS(ns, 'rect');
+    mark.setAttribute('x', '30');
+    mark.setAttribute('y', '42');
+    mark.setAttribute('width', '39');
+    mark.setAttribute('height', '15');
+  }
+  mark.classList.add('effect', effect.toLowerCase());
+  return mark;
+}
+
 const PORT_LOCATIONS = {
   A: [33, 0],
   B: [66, 0],
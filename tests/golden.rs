@@ -0,0 +1,48 @@
+// Replays each script in tests/golden/ and compares the outcome against the
+// .txt file with the same name. Run with UPDATE_GOLDEN=1 to rewrite the
+// expected outputs after an intended rule change.
+use std::fs;
+use std::path::Path;
+use strecke::script::GameScript;
+
+#[test]
+fn golden_games() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "No scripts in {}", dir.display());
+    let mut failures = Vec::new();
+    for path in paths {
+        let json = fs::read_to_string(&path).unwrap();
+        let script: GameScript = serde_json::from_str(&json)
+            .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        let report = match script.run() {
+            Ok(outcome) => outcome.report(),
+            Err(e) => format!("error: {}\n", e),
+        };
+        let golden = path.with_extension("txt");
+        if update {
+            fs::write(&golden, &report).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&golden).unwrap_or_default();
+        if expected != report {
+            failures.push(format!(
+                "{}\n--- expected\n{}--- actual\n{}",
+                path.display(),
+                expected,
+                report
+            ));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "Golden outputs differ (rerun with UPDATE_GOLDEN=1 to accept):\n{}",
+        failures.join("\n")
+    );
+}
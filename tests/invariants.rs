@@ -0,0 +1,130 @@
+// Randomized checks of rules that should hold at every point in any game.
+use proptest::prelude::*;
+use proptest::sample::subsequence;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::time::Instant;
+use strecke::agent::{Agent, AvoidSuddenDeathAgent};
+use strecke::board::{Board, Position, edge_position};
+use strecke::game::GameManager;
+use strecke::tiles::{Direction, Port};
+
+const NUM_TILES: usize = 35;
+
+fn cell(
+    board: &Board,
+    row: i8,
+    col: i8,
+) -> Option<(strecke::tiles::Tile, Direction)> {
+    let pos = Position {
+        row,
+        col,
+        port: Port::A,
+        alive: true,
+    };
+    board.get_tile(&pos).copied().flatten()
+}
+
+fn tiles_on_board(board: &Board) -> usize {
+    (0..6)
+        .flat_map(|row| (0..6).map(move |col| (row, col)))
+        .filter(|&(row, col)| cell(board, row, col).is_some())
+        .count()
+}
+
+fn tile_count(game: &GameManager) -> usize {
+    let in_hands: usize = game
+        .alive_players
+        .iter()
+        .map(|p| p.tiles_in_hand.len())
+        .sum();
+    in_hands + game.tiles_remaining() + tiles_on_board(&game.board)
+}
+
+// Each step of a trail follows from the one before it.
+fn check_trail(board: &Board, trail: &[Position]) -> Result<(), TestCaseError> {
+    for (i, pair) in trail.windows(2).enumerate() {
+        let (a, b) = (&pair[0], &pair[1]);
+        prop_assert!(a.alive, "trail continues after death at step {}", i);
+        let next = a.next_tile_position();
+        prop_assert_eq!((b.row, b.col), (next.row, next.col));
+        if let Some((tile, facing)) = cell(board, b.row, b.col) {
+            prop_assert_eq!(tile.traverse(next.port, facing), b.port);
+        } else {
+            // Walking off the board is the only way to leave the grid.
+            prop_assert!(!b.alive);
+            prop_assert_eq!(b.port, next.port);
+        }
+    }
+    Ok(())
+}
+
+fn play_game(
+    seed: u64,
+    seats: &[i8],
+    moves: &[Option<(usize, u8)>],
+) -> Result<(), TestCaseError> {
+    let mut game = GameManager::new(&mut StdRng::seed_from_u64(seed));
+    // Crowded starts are still worth checking.
+    game.set_min_start_spacing(0).unwrap();
+    for (i, &seat) in seats.iter().enumerate() {
+        game.register_player(format!("p{}", i), edge_position(seat))
+            .unwrap();
+    }
+    prop_assert_eq!(tile_count(&game), NUM_TILES);
+    let agent = AvoidSuddenDeathAgent;
+    let mut num_moves = 0;
+    for choice in moves.iter() {
+        if game.is_over() {
+            break;
+        }
+        let obs = game.current_observation();
+        let (tile_idx, facing) = match *choice {
+            Some((idx, dir)) => {
+                let facing = Direction::all().nth(dir as usize).unwrap();
+                (idx % obs.hand.len(), facing)
+            }
+            None => agent.choose_action(&obs, Instant::now()),
+        };
+        // The tile always goes into an empty cell.
+        let target = obs.position().next_tile_position();
+        prop_assert!(cell(&game.board, target.row, target.col).is_none());
+        let before = game.board.clone();
+        let placed = tiles_on_board(&before);
+
+        game.take_turn(tile_idx, facing).unwrap();
+        num_moves += 1;
+
+        prop_assert_eq!(tiles_on_board(&game.board), placed + 1);
+        prop_assert_eq!(tile_count(&game), NUM_TILES);
+        for (old, new) in before.players.iter().zip(game.board.players.iter()) {
+            if !old.last().unwrap().alive {
+                prop_assert_eq!(old, new, "a dead player moved");
+            }
+            prop_assert_eq!(&new[..old.len()], &old[..]);
+            check_trail(&game.board, new)?;
+        }
+        for p in game.alive_players.iter() {
+            prop_assert!(p.tiles_in_hand.len() <= 3);
+        }
+    }
+    prop_assert!(game.is_over(), "game still going after {} moves", num_moves);
+    prop_assert!(num_moves <= NUM_TILES);
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn invariants_hold_in_random_games(
+        seed in any::<u64>(),
+        seats in subsequence((0..48).collect::<Vec<i8>>(), 2..=8)
+            .prop_shuffle(),
+        // None lets a sensible agent move, so games aren't all short.
+        moves in prop::collection::vec(
+            prop::option::weighted(0.3, (0..3usize, 0..4u8)),
+            NUM_TILES,
+        ),
+    ) {
+        play_game(seed, &seats, &moves)?;
+    }
+}